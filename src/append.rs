@@ -0,0 +1,180 @@
+//! Contains the textual insertion of a bookmark into a rendered document, without parsing it.
+use askama::Template;
+use kuchiki::parse_html;
+use kuchiki::traits::TendrilSink;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use crate::escape::escape_bookmark;
+use crate::render::{EscapePolicy, RenderOptions};
+use crate::Bookmark;
+
+/// Inserts the rendered `bookmark` at the end of the folder found by its titles `path_in_tree` in the file.
+/// The bookmark is escaped like the rendered documents, with the default [EscapePolicy].
+pub(crate) fn append_bookmark_to_file(
+    path: &Path,
+    path_in_tree: &[&str],
+    bookmark: &Bookmark,
+) -> Result<(), Error> {
+    let raw = fs::read_to_string(path)?;
+    let rendered = escape_bookmark(bookmark, EscapePolicy::default())
+        .render()
+        .map_err(Error::other)?;
+    let rendered = RenderOptions::default().apply(&rendered);
+
+    match insert_bookmark(&raw, path_in_tree, &rendered) {
+        Some(inserted) => fs::write(path, inserted),
+        None => Err(Error::new(
+            ErrorKind::NotFound,
+            format!("the folder {:?} isn't in the document", path_in_tree),
+        )),
+    }
+}
+
+/// Inserts a rendered item before the closing `</DL>` of the folder, on its own line.
+pub(crate) fn insert_bookmark(raw: &str, path_in_tree: &[&str], rendered: &str) -> Option<String> {
+    let closing = closing_list(raw, path_in_tree)?;
+    let line_start = raw[..closing].rfind('\n').map_or(0, |i| i + 1);
+    let indent = &raw[line_start..closing];
+
+    let mut inserted = String::with_capacity(raw.len() + rendered.len() + indent.len() + 5);
+
+    if indent.chars().all(char::is_whitespace) {
+        inserted.push_str(&raw[..line_start]);
+        inserted.push_str(indent);
+        inserted.push_str("    ");
+        inserted.push_str(rendered);
+        inserted.push('\n');
+        inserted.push_str(&raw[line_start..]);
+    } else {
+        inserted.push_str(&raw[..closing]);
+        inserted.push('\n');
+        inserted.push_str(rendered);
+        inserted.push('\n');
+        inserted.push_str(&raw[closing..]);
+    }
+
+    Some(inserted)
+}
+
+/// Finds the offset of the closing `</DL>` of the folder, by following the `<H3/>`, `<DL>` and `</DL>` tags.
+/// The comments are skipped and the tag names are matched case-insensitively.
+/// The titles are decoded like the parser does, and an `<H3/>` only names the `<DL>` that follows it in the same list.
+fn closing_list(raw: &str, path_in_tree: &[&str]) -> Option<usize> {
+    let upper = raw.to_ascii_uppercase();
+    let mut stack: Vec<Option<String>> = vec![];
+    let mut pending_title = None;
+    let mut offset = 0;
+
+    while let Some(found) = upper[offset..].find('<') {
+        let start = offset + found;
+        let rest = &upper[start..];
+
+        if rest.starts_with("<!--") {
+            offset = start + rest.find("-->").map_or(rest.len(), |end| end + 3);
+            continue;
+        }
+
+        let end = start + rest.find('>').unwrap_or(rest.len());
+
+        if tag_is(rest, "<H3") {
+            let text_end = upper[end..].find("</H3").map_or(upper.len(), |i| end + i);
+            pending_title = Some(decode_title(&raw[(end + 1).min(text_end)..text_end]));
+        } else if tag_is(rest, "<DL") {
+            stack.push(pending_title.take());
+        } else if tag_is(rest, "</DL") {
+            pending_title = None;
+
+            let titles: Vec<&str> = stack
+                .iter()
+                .skip(1)
+                .map(|title| title.as_deref().unwrap_or(""))
+                .collect();
+
+            if !stack.is_empty() && titles == path_in_tree {
+                return Some(start);
+            }
+
+            stack.pop();
+        }
+
+        offset = end;
+    }
+
+    None
+}
+
+/// Checks if the tag at the start of `rest` is named `name`, and not a longer name.
+fn tag_is(rest: &str, name: &str) -> bool {
+    rest.starts_with(name)
+        && rest[name.len()..]
+            .chars()
+            .next()
+            .is_none_or(|c| !c.is_ascii_alphanumeric())
+}
+
+/// Decodes the raw HTML of a folder title as its text, e.g. the character references and the inline markup.
+fn decode_title(raw: &str) -> String {
+    parse_html().one(raw).text_contents()
+}
+
+#[test]
+fn should_insert_before_the_folder_closing_list() {
+    let raw = r#"<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+    <DT><H3>Work &amp; co</H3>
+    <DL><p>
+        <DT><A HREF="a">a</A>
+    </DL><p>
+    <DT><H3>Dl</H3>
+    <DL><p>
+    </DL><p>
+    <!-- </DL> -->
+</DL><p>"#;
+
+    let work = insert_bookmark(raw, &["Work & co"], "<DT>new").unwrap();
+    let root = insert_bookmark(raw, &[], "<DT>new").unwrap();
+
+    assert!(work.contains("<A HREF=\"a\">a</A>\n        <DT>new\n    </DL><p>\n    <DT><H3>Dl"));
+    assert!(root.ends_with("<!-- </DL> -->\n    <DT>new\n</DL><p>"));
+    assert_eq!(insert_bookmark(raw, &["Missing"], "<DT>new"), None);
+}
+
+#[test]
+fn should_decode_titles_and_forget_folders_without_list() {
+    let raw = r#"<DL><p>
+    <DT><H3>Tom&#39;s &#x26; <b>co</b></H3>
+    <DL><p>
+        <DT><H3>Empty</H3>
+    </DL><p>
+    <DL><p>
+    </DL><p>
+</DL><p>"#;
+
+    let toms = insert_bookmark(raw, &["Tom's & co"], "<DT>new").unwrap();
+
+    assert!(toms.contains("<H3>Empty</H3>\n        <DT>new\n    </DL>"));
+    assert_eq!(insert_bookmark(raw, &["Empty"], "<DT>new"), None);
+}
+
+#[test]
+fn should_insert_into_deeply_nested_folder() {
+    use crate::Netscape;
+
+    let raw = fs::read_to_string("./res/chromium_nested.html").unwrap();
+    let path = ["Bookmarks bar", "Rust", "Crates"];
+    let bookmark = Bookmark::from(("serde", "https://serde.rs/"));
+    let inserted = insert_bookmark(&raw, &path, &bookmark.render().unwrap()).unwrap();
+    let netscape = Netscape::from_html(&inserted).unwrap();
+
+    assert_eq!(
+        netscape.path_of(&bookmark),
+        Some(path.iter().map(|t| t.to_string()).collect())
+    );
+    assert_eq!(
+        netscape.get_bookmarks().len(),
+        Netscape::from_html(&raw).unwrap().get_bookmarks().len() + 1
+    );
+}
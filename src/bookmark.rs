@@ -1,14 +1,22 @@
 //! Contains the [Bookmark] model and its associated tests.
+#[cfg(feature = "render")]
 use askama::Template;
+use chrono::{DateTime, Utc};
 use kuchiki::NodeRef;
 use serde::Serialize;
+use std::hash::{Hash, Hasher};
 
+use crate::date::parse_date;
 use crate::node_ref_ext::*;
+use crate::parse::ParseOptions;
+#[cfg(feature = "render")]
+use crate::render::sort_by_attribute_order;
 
 /// Implements the specification of the `shortcut` item.
-#[derive(Serialize, Builder, Clone, Debug, Default, Template)]
+#[derive(Serialize, Builder, Clone, Debug, Default)]
+#[cfg_attr(feature = "render", derive(Template))]
 #[builder(setter(into))]
-#[template(path = "bookmark.j2", escape = "none")]
+#[cfg_attr(feature = "render", template(path = "bookmark.j2", escape = "none"))]
 pub struct Bookmark {
     /// The `href` attribute stores the url to the page of the shortcut.
     pub href: String,
@@ -36,11 +44,33 @@ pub struct Bookmark {
     /// It is usually a png encoded in base64.
     #[builder(default)]
     pub icon: String,
+
+    /// The `target` attribute is the browsing context where the shortcut is opened, e.g. `_blank`.
+    #[builder(default)]
+    pub target: String,
+
+    /// The `rel` attribute is the relationship of the linked page, e.g. `noopener`.
+    #[builder(default)]
+    pub rel: String,
+
+    /// The `shortcut_url` attribute is the keyword that opens the shortcut from the address bar, e.g. `wiki`.
+    /// The `%s` placeholder of the `href` is then replaced by the searched terms.
+    #[builder(default)]
+    pub shortcut_url: String,
+
+    /// The `description` of the shortcut is the text of the `<DD/>` element next to its `<DT/>`.
+    #[builder(default)]
+    pub description: String,
 }
 
 impl Bookmark {
     /// Creates a [Bookmark] model from a parsed HTML DOM.
     pub fn from_node(node: &NodeRef) -> Option<Self> {
+        Bookmark::from_node_with(node, &ParseOptions::default())
+    }
+
+    /// Creates a [Bookmark] model from a parsed HTML DOM, customized by the [ParseOptions].
+    pub fn from_node_with(node: &NodeRef, options: &ParseOptions) -> Option<Self> {
         let mut bookmark = None;
         let mut builder = BookmarkBuilder::default();
 
@@ -48,22 +78,22 @@ impl Bookmark {
             let a = node.children().find(|n| n.is_element("A"));
 
             if let Some(node) = a {
-                bookmark = Bookmark::from_node(&node);
+                bookmark = Bookmark::from_node_with(&node, options);
             }
         } else if node.is_element("A") {
             if let Some(attribute) = node.select_attribute("HREF") {
                 builder.href(attribute.value);
             }
 
-            if let Some(attribute) = node.select_attribute("ADD_DATE") {
+            if let Some(attribute) = select_date_attribute(node, "ADD_DATE") {
                 builder.add_date(attribute.value);
             }
 
-            if let Some(attribute) = node.select_attribute("LAST_VISIT") {
+            if let Some(attribute) = select_date_attribute(node, "LAST_VISIT") {
                 builder.last_visit(attribute.value);
             }
 
-            if let Some(attribute) = node.select_attribute("LAST_MODIFIED") {
+            if let Some(attribute) = select_date_attribute(node, "LAST_MODIFIED") {
                 builder.last_modified(attribute.value);
             }
 
@@ -75,15 +105,161 @@ impl Bookmark {
                 builder.icon(attribute.value);
             }
 
+            if let Some(attribute) = node.select_attribute("TARGET") {
+                builder.target(attribute.value);
+            }
+
+            if let Some(attribute) = node.select_attribute("REL") {
+                builder.rel(attribute.value);
+            }
+
+            if let Some(attribute) = node.select_attribute("SHORTCUTURL") {
+                builder.shortcut_url(attribute.value);
+            }
+
+            let description = node
+                .parent()
+                .filter(|parent| parent.is_element("DT"))
+                .and_then(|dt| select_description(&dt));
+
+            if let Some(description) = description {
+                builder.description(description);
+            }
+
             builder.title(node.text_contents());
 
             if let Ok(built) = builder.build() {
-                bookmark = Some(built);
+                if options.allows_href(&built.href) {
+                    bookmark = Some(built);
+                }
             }
         }
 
         bookmark
     }
+
+    /// Gets the `add_date` attribute as a date, it is [None] when the attribute is empty or malformed.
+    pub fn added(&self) -> Option<DateTime<Utc>> {
+        parse_date(&self.add_date)
+    }
+
+    /// Gets the `last_modified` attribute as a date, it is [None] when the attribute is empty or malformed.
+    pub fn modified(&self) -> Option<DateTime<Utc>> {
+        parse_date(&self.last_modified)
+    }
+
+    /// Gets the [BookmarkKey] of the [Bookmark], to deduplicate the bookmarks regardless of their dates.
+    ///
+    /// ```rust
+    /// use bookmarkt::Bookmark;
+    /// use std::collections::HashSet;
+    ///
+    /// let saved = Bookmark::from(("Rust", "https://www.rust-lang.org/"));
+    /// let saved_again = saved.clone().with_add_date("1600910474");
+    /// let links: HashSet<_> = vec![&saved, &saved_again].into_iter().map(Bookmark::key).collect();
+    ///
+    /// assert_eq!(links.len(), 1);
+    /// ```
+    pub fn key(&self) -> BookmarkKey {
+        BookmarkKey {
+            href: self.href.clone(),
+            title: self.title.clone(),
+        }
+    }
+
+    /// Returns the [Bookmark] with its `title` replaced.
+    ///
+    /// ```rust
+    /// use bookmarkt::Bookmark;
+    ///
+    /// let bookmark = Bookmark::from(("Rust", "https://www.rust-lang.org/"))
+    ///     .with_title("The Rust Programming Language")
+    ///     .with_href("https://doc.rust-lang.org/book/")
+    ///     .with_add_date("1600910474");
+    ///
+    /// assert_eq!(bookmark.title, "The Rust Programming Language");
+    /// assert_eq!(bookmark.href, "https://doc.rust-lang.org/book/");
+    /// assert_eq!(bookmark.add_date, "1600910474");
+    /// ```
+    pub fn with_title(self, title: impl Into<String>) -> Self {
+        Bookmark {
+            title: title.into(),
+            ..self
+        }
+    }
+
+    /// Returns the [Bookmark] with its `href` replaced.
+    pub fn with_href(self, href: impl Into<String>) -> Self {
+        Bookmark {
+            href: href.into(),
+            ..self
+        }
+    }
+
+    /// Returns the [Bookmark] with its `add_date` replaced.
+    pub fn with_add_date(self, add_date: impl Into<String>) -> Self {
+        Bookmark {
+            add_date: add_date.into(),
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "render")]
+impl Bookmark {
+    /// Renders the attributes of the `<A/>` tag, in the order of the Firefox exports or in the `attribute_order`
+    /// of the [RenderOptions] being applied. The `HREF` and the `ADD_DATE` are always rendered, the others when set.
+    ///
+    /// [RenderOptions]: crate::RenderOptions
+    fn anchor_attributes(&self) -> String {
+        let mut attributes = vec![("HREF", &self.href), ("ADD_DATE", &self.add_date)];
+        let optional = [
+            ("LAST_VISIT", &self.last_visit),
+            ("LAST_MODIFIED", &self.last_modified),
+            ("ICON_URI", &self.icon_uri),
+            ("ICON", &self.icon),
+            ("TARGET", &self.target),
+            ("REL", &self.rel),
+            ("SHORTCUTURL", &self.shortcut_url),
+        ];
+        attributes.extend(optional.iter().filter(|(_, value)| !value.is_empty()));
+
+        sort_by_attribute_order(&mut attributes, |(name, _)| name);
+
+        attributes
+            .iter()
+            .map(|(name, value)| format!(" {}=\"{}\"", name, value))
+            .collect()
+    }
+}
+
+/// Gets the text of the `<DD/>` element of a `<DT/>`, either among its children or as its next sibling element.
+fn select_description(dt: &NodeRef) -> Option<String> {
+    let dd = dt.children().find(|n| n.is_element("DD")).or_else(|| {
+        dt.following_siblings()
+            .find(|n| n.as_element().is_some())
+            .filter(|n| n.is_element("DD"))
+    });
+
+    dd.map(|dd| dd.text_contents().trim().to_string())
+}
+
+/// Creates a [Bookmark] from a `(title, href)` pair.
+impl From<(&str, &str)> for Bookmark {
+    fn from((title, href): (&str, &str)) -> Self {
+        Bookmark::from((String::from(title), String::from(href)))
+    }
+}
+
+/// Creates a [Bookmark] from a `(title, href)` pair.
+impl From<(String, String)> for Bookmark {
+    fn from((title, href): (String, String)) -> Self {
+        Bookmark {
+            href,
+            title,
+            ..Bookmark::default()
+        }
+    }
 }
 
 impl PartialEq for Bookmark {
@@ -96,6 +272,27 @@ impl PartialEq for Bookmark {
     }
 }
 
+/// Two [Bookmark]s are equal when their `href`, `title` and dates are, use the [BookmarkKey] to ignore the dates.
+impl Eq for Bookmark {}
+
+/// Hashes the `href` and the `title` of the [Bookmark], the equal bookmarks share the same hash.
+impl Hash for Bookmark {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.href.hash(state);
+        self.title.hash(state);
+    }
+}
+
+/// Identifies a [Bookmark] by its `href` and its `title` only, e.g. to deduplicate the copies saved at other dates.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BookmarkKey {
+    /// The `href` of the bookmark.
+    pub href: String,
+
+    /// The `title` of the bookmark.
+    pub title: String,
+}
+
 #[allow(dead_code)]
 fn mock_bookmark() -> Bookmark {
     Bookmark {
@@ -106,9 +303,14 @@ fn mock_bookmark() -> Bookmark {
         title: String::from("name"),
         icon_uri: String::from(""),
         icon: String::from("icon"),
+        target: String::from(""),
+        rel: String::from(""),
+        shortcut_url: String::from(""),
+        description: String::from(""),
     }
 }
 
+#[cfg(feature = "render")]
 #[test]
 fn should_render_bookmark_html() {
     let rendered = r#"<DT><A HREF="url" ADD_DATE="date" LAST_VISIT="date" LAST_MODIFIED="date" ICON="icon">name</A>"#;
@@ -125,13 +327,138 @@ fn should_parse_netscape_bookmark() {
 LAST_MODIFIED="date" ICON="icon">name</A>"#;
     let a = parse_html().one(item).select_first("A").unwrap();
 
-    assert_eq!(Bookmark::from_node(&a.as_node()).unwrap(), mock_bookmark())
+    assert_eq!(Bookmark::from_node(a.as_node()).unwrap(), mock_bookmark())
 }
 
 #[test]
 fn should_serialize_json_bookmark() {
-    let json = r#"{"href":"url","title":"name","add_date":"date","last_visit":"date","last_modified":"date","icon_uri":"","icon":"icon"}"#;
+    let json = r#"{"href":"url","title":"name","add_date":"date","last_visit":"date","last_modified":"date","icon_uri":"","icon":"icon","target":"","rel":"","shortcut_url":"","description":""}"#;
     let bookmark = mock_bookmark();
 
     assert_eq!(serde_json::to_string(&bookmark).unwrap(), json)
 }
+
+#[test]
+fn should_hash_bookmark_identity() {
+    use std::collections::HashSet;
+
+    let mut visited = mock_bookmark();
+    visited.icon = String::from("other");
+
+    let mut moved = mock_bookmark();
+    moved.href = String::from("other");
+
+    let set: HashSet<Bookmark> = vec![mock_bookmark(), visited, moved].into_iter().collect();
+
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&mock_bookmark()));
+}
+
+#[test]
+fn should_convert_bookmark_from_pair() {
+    let bookmark = Bookmark::from(("name", "url"));
+
+    assert_eq!(bookmark.title, "name");
+    assert_eq!(bookmark.href, "url");
+    assert_eq!(bookmark, (String::from("name"), String::from("url")).into());
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_roundtrip_bookmark_target_and_rel() {
+    use kuchiki::parse_html;
+    use kuchiki::traits::TendrilSink;
+
+    let item = r#"<DT><A HREF="url" ADD_DATE="date" TARGET="_blank" REL="noopener">name</A>"#;
+    let a = parse_html().one(item).select_first("A").unwrap();
+    let bookmark = Bookmark::from_node(a.as_node()).unwrap();
+
+    assert_eq!(bookmark.target, "_blank");
+    assert_eq!(bookmark.rel, "noopener");
+    assert_eq!(bookmark.render().unwrap(), item);
+}
+
+#[test]
+fn should_skip_disallowed_bookmark_scheme() {
+    use kuchiki::parse_html;
+    use kuchiki::traits::TendrilSink;
+
+    let options = ParseOptions {
+        allowed_schemes: Some(vec![String::from("https")]),
+        ..ParseOptions::default()
+    };
+
+    let item = r#"<DT><A HREF="javascript:alert(1)">name</A>"#;
+    let dt = parse_html().one(item).select_first("DT").unwrap();
+
+    assert!(Bookmark::from_node(dt.as_node()).is_some());
+    assert!(Bookmark::from_node_with(dt.as_node(), &options).is_none());
+}
+
+#[test]
+fn should_parse_description_inside_and_after_dt() {
+    use kuchiki::parse_html;
+    use kuchiki::traits::TendrilSink;
+
+    let document = parse_html().one(std::fs::read_to_string("./res/descriptions.html").unwrap());
+    let descriptions: Vec<String> = document
+        .select_elements("DT")
+        .iter()
+        .filter_map(Bookmark::from_node)
+        .map(|b| b.description)
+        .collect();
+
+    assert_eq!(descriptions, vec!["Inside the DT", "After the DT", ""]);
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_roundtrip_bookmark_shortcut_url() {
+    use kuchiki::parse_html;
+    use kuchiki::traits::TendrilSink;
+
+    let item = r#"<DT><A HREF="https://crates.io/search?q=%s" ADD_DATE="date" SHORTCUTURL="crate">crates.io</A>"#;
+    let a = parse_html().one(item).select_first("A").unwrap();
+    let bookmark = Bookmark::from_node(a.as_node()).unwrap();
+
+    assert_eq!(bookmark.shortcut_url, "crate");
+    assert_eq!(bookmark.render().unwrap(), item);
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_render_firefox_anchors_in_firefox_order() {
+    let raw = std::fs::read_to_string("./res/firefox.html").unwrap();
+    let anchors: Vec<&str> = raw
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("<DT><A "))
+        .collect();
+
+    assert!(!anchors.is_empty());
+
+    for line in anchors {
+        let item = crate::item::Item::from_html(line).unwrap();
+        let bookmark = item.take_shortcut().unwrap();
+
+        assert_eq!(bookmark.render().unwrap(), line);
+    }
+}
+
+#[test]
+fn should_distinguish_bookmarks_by_dates() {
+    use std::collections::HashSet;
+
+    let mut revisited = mock_bookmark();
+    revisited.last_visit = String::from("later");
+
+    let set: HashSet<Bookmark> = vec![mock_bookmark(), revisited.clone()]
+        .into_iter()
+        .collect();
+    let hrefs: HashSet<&str> = set.iter().map(|b| b.href.as_str()).collect();
+
+    assert_ne!(mock_bookmark(), revisited);
+    assert_eq!(set.len(), 2);
+    assert_eq!(hrefs.len(), 1);
+    assert_eq!(mock_bookmark().key(), revisited.key());
+}
@@ -0,0 +1,149 @@
+//! Contains the import of the `Bookmarks` JSON file of a Chrome profile.
+use serde_json::Value;
+use std::io::{Error, ErrorKind};
+
+use crate::item::Item;
+use crate::{Bookmark, Folder, Netscape};
+
+/// The seconds between the Windows epoch (1601-01-01) of the Chrome dates and the UNIX epoch.
+const WINDOWS_EPOCH_OFFSET: i64 = 11_644_473_600;
+
+/// Reads the `roots` of a Chrome `Bookmarks` file, as in the Chrome exports :
+/// the bookmark bar is the toolbar folder, the other bookmarks are imported at the root,
+/// and the synced bookmarks are kept in their folder when there are some.
+pub(crate) fn from_chrome_json(raw: &str) -> Result<Netscape, Error> {
+    let json: Value = serde_json::from_str(raw)?;
+    let roots = json
+        .get("roots")
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "the document has no Chrome roots"))?;
+
+    let mut children = vec![];
+
+    if let Some(Item::Subfolder(mut bar)) = roots.get("bookmark_bar").and_then(build_item) {
+        bar.personal_toolbar_folder = true;
+        children.push(Item::Subfolder(bar));
+    }
+
+    if let Some(Item::Subfolder(other)) = roots.get("other").and_then(build_item) {
+        children.extend(other.children);
+    }
+
+    if let Some(Item::Subfolder(synced)) = roots.get("synced").and_then(build_item) {
+        if !synced.children.is_empty() {
+            children.push(Item::Subfolder(synced));
+        }
+    }
+
+    Ok(Netscape {
+        title: String::from("Bookmarks"),
+        h1: String::from("Bookmarks"),
+        children,
+        ..Netscape::default()
+    })
+}
+
+/// Builds an item from a node of `type` `folder` or `url`, the other nodes are ignored.
+fn build_item(node: &Value) -> Option<Item> {
+    let string = |key: &str| {
+        node.get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    match node.get("type").and_then(Value::as_str) {
+        Some("folder") => Some(Item::Subfolder(Folder {
+            title: string("name"),
+            add_date: seconds(&string("date_added")),
+            last_modified: seconds(&string("date_modified")),
+            children: node
+                .get("children")
+                .and_then(Value::as_array)
+                .map(|children| children.iter().filter_map(build_item).collect())
+                .unwrap_or_default(),
+            ..Folder::default()
+        })),
+        Some("url") => Some(Item::Shortcut(Bookmark {
+            href: string("url"),
+            title: string("name"),
+            add_date: seconds(&string("date_added")),
+            last_visit: seconds(&string("date_last_used")),
+            ..Bookmark::default()
+        })),
+        _ => None,
+    }
+}
+
+/// Converts the microseconds since 1601 of Chrome into the seconds timestamps of the Netscape format.
+/// The `0` dates, that Chrome writes for the missing ones, are empty.
+fn seconds(microseconds: &str) -> String {
+    microseconds
+        .parse::<i64>()
+        .ok()
+        .map(|us| us / 1_000_000 - WINDOWS_EPOCH_OFFSET)
+        .filter(|seconds| *seconds > 0)
+        .map(|seconds| seconds.to_string())
+        .unwrap_or_default()
+}
+
+#[test]
+fn should_import_chrome_json() {
+    let raw = r#"{
+   "checksum": "0",
+   "roots": {
+      "bookmark_bar": {
+         "children": [ {
+            "date_added": "13245384526000000",
+            "date_last_used": "0",
+            "id": "5",
+            "name": "Rust",
+            "type": "url",
+            "url": "https://www.rust-lang.org/"
+         } ],
+         "date_added": "13245384474000000",
+         "date_modified": "13245384622000000",
+         "id": "1",
+         "name": "Bookmarks bar",
+         "type": "folder"
+      },
+      "other": {
+         "children": [ {
+            "children": [],
+            "date_added": "13245384659000000",
+            "id": "6",
+            "name": "References",
+            "type": "folder"
+         } ],
+         "date_added": "0",
+         "id": "2",
+         "name": "Other bookmarks",
+         "type": "folder"
+      },
+      "synced": {
+         "children": [],
+         "id": "3",
+         "name": "Mobile bookmarks",
+         "type": "folder"
+      }
+   },
+   "version": 1
+}"#;
+
+    let netscape = from_chrome_json(raw).unwrap();
+
+    match &netscape.children[..] {
+        [Item::Subfolder(bar), Item::Subfolder(references)] => {
+            assert!(bar.personal_toolbar_folder);
+            assert_eq!(bar.add_date, "1600910874");
+            assert_eq!(bar.last_modified, "1600911022");
+            assert!(
+                matches!(&bar.children[..], [Item::Shortcut(rust)] if rust.add_date == "1600910926" && rust.last_visit.is_empty())
+            );
+            assert_eq!(references.title, "References");
+        }
+        _ => panic!("unexpected children"),
+    }
+
+    assert!(from_chrome_json("{}").is_err());
+    assert!(from_chrome_json("<DL>").is_err());
+}
@@ -0,0 +1,132 @@
+//! Contains the split of the items into chunks of a bounded number of bookmarks, for the importers.
+use crate::item::Item;
+use crate::Folder;
+
+/// Splits the `children` into chunks of at most `limit` bookmarks, in the document order.
+///
+/// The folders are recreated in every chunk that holds some of their bookmarks, the empty folders are kept
+/// in the chunk that is filled when they are reached. A `limit` of `0` is read as `1`.
+pub(crate) fn chunk_children(children: &[Item], limit: usize) -> Vec<Vec<Item>> {
+    let mut chunker = Chunker {
+        limit: limit.max(1),
+        chunks: vec![vec![]],
+        count: 0,
+        path: vec![],
+    };
+
+    chunker.visit(children);
+    chunker.chunks
+}
+
+struct Chunker<'a> {
+    limit: usize,
+    chunks: Vec<Vec<Item>>,
+    count: usize,
+    /// The folders that are open in the current chunk, from the root.
+    path: Vec<&'a Folder>,
+}
+
+impl<'a> Chunker<'a> {
+    fn visit(&mut self, children: &'a [Item]) {
+        for child in children {
+            match child {
+                Item::Subfolder(f) => {
+                    self.current().push(Item::Subfolder(f.without_children()));
+                    self.path.push(f);
+                    self.visit(&f.children);
+                    self.path.pop();
+                }
+                Item::Shortcut(b) => {
+                    if self.count == self.limit {
+                        self.start_chunk();
+                    }
+
+                    self.current().push(Item::Shortcut(b.clone()));
+                    self.count += 1;
+                }
+            }
+        }
+    }
+
+    /// Starts a new chunk, with a copy of the open folders.
+    fn start_chunk(&mut self) {
+        let mut chunk = vec![];
+        let mut children = &mut chunk;
+
+        for folder in &self.path {
+            children.push(Item::Subfolder(folder.without_children()));
+
+            children = match children.last_mut() {
+                Some(Item::Subfolder(f)) => &mut f.children,
+                _ => unreachable!(),
+            };
+        }
+
+        self.chunks.push(chunk);
+        self.count = 0;
+    }
+
+    /// Gets the children of the innermost open folder of the current chunk, it is always its last item.
+    fn current(&mut self) -> &mut Vec<Item> {
+        let mut children = self.chunks.last_mut().expect("at least one chunk");
+
+        for _ in 0..self.path.len() {
+            children = match children.last_mut() {
+                Some(Item::Subfolder(f)) => &mut f.children,
+                _ => unreachable!(),
+            };
+        }
+
+        children
+    }
+}
+
+#[test]
+fn should_chunk_children_and_recreate_folders() {
+    use crate::Bookmark;
+
+    let children = vec![
+        Item::Shortcut(Bookmark::from(("a", "url"))),
+        Item::Subfolder(Folder {
+            title: String::from("f"),
+            children: vec![
+                Item::Shortcut(Bookmark::from(("b", "url"))),
+                Item::Shortcut(Bookmark::from(("c", "url"))),
+            ],
+            ..Folder::default()
+        }),
+        Item::Subfolder(Folder {
+            title: String::from("empty"),
+            ..Folder::default()
+        }),
+    ];
+
+    let chunks = chunk_children(&children, 2);
+
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(
+        chunks[0],
+        vec![
+            Item::Shortcut(Bookmark::from(("a", "url"))),
+            Item::Subfolder(Folder {
+                title: String::from("f"),
+                children: vec![Item::Shortcut(Bookmark::from(("b", "url")))],
+                ..Folder::default()
+            }),
+        ]
+    );
+    assert_eq!(
+        chunks[1],
+        vec![
+            Item::Subfolder(Folder {
+                title: String::from("f"),
+                children: vec![Item::Shortcut(Bookmark::from(("c", "url")))],
+                ..Folder::default()
+            }),
+            Item::Subfolder(Folder {
+                title: String::from("empty"),
+                ..Folder::default()
+            }),
+        ]
+    );
+}
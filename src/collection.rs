@@ -11,6 +11,24 @@ pub trait NestedCollection {
 
     /// Collects all nested [Folder]s of the folder
     fn subfolders(&self) -> Vec<&Folder>;
+
+    /// Collects all nested [Bookmark]s of the folder with the titles of their parent folders
+    fn shortcuts_with_path(&self) -> Vec<(Vec<&str>, &Bookmark)>;
+
+    /// Collects all nested [Bookmark]s of the folder as mutable references
+    fn shortcuts_mut(&mut self) -> Vec<&mut Bookmark>;
+
+    /// Sorts the [Bookmark]s of every nested folder by the key, the [Folder]s keep their positions
+    fn sort_shortcuts_by_key<K: Ord, F: Fn(&Bookmark) -> K>(&mut self, key: &F);
+
+    /// Sorts the [Item]s of every nested folder by the key, the sort is stable
+    fn sort_items_by_key<K: Ord, F: Fn(&Item) -> K>(&mut self, key: &F);
+
+    /// Gets the children of the nested folder found by its titles path, the missing folders are created
+    fn children_at_path(&mut self, path: &[&str]) -> &mut Vec<Item>;
+
+    /// Removes all nested [Bookmark]s matching the predicate and returns them in the document order
+    fn drain_shortcuts<F: Fn(&Bookmark) -> bool>(&mut self, predicate: &F) -> Vec<Bookmark>;
 }
 
 impl NestedCollection for Vec<Item> {
@@ -39,6 +57,175 @@ impl NestedCollection for Vec<Item> {
 
         all
     }
+
+    fn shortcuts_with_path(&self) -> Vec<(Vec<&str>, &Bookmark)> {
+        let mut all = vec![];
+
+        for item in self.iter() {
+            match item {
+                Item::Subfolder(f) => {
+                    for (mut path, b) in f.children.shortcuts_with_path() {
+                        path.insert(0, f.title.as_str());
+                        all.push((path, b));
+                    }
+                }
+                Item::Shortcut(b) => all.push((vec![], b)),
+            }
+        }
+
+        all
+    }
+
+    fn shortcuts_mut(&mut self) -> Vec<&mut Bookmark> {
+        let mut all = vec![];
+
+        for item in self.iter_mut() {
+            match item {
+                Item::Subfolder(f) => all.append(&mut f.children.shortcuts_mut()),
+                Item::Shortcut(b) => all.push(b),
+            }
+        }
+
+        all
+    }
+
+    fn sort_shortcuts_by_key<K: Ord, F: Fn(&Bookmark) -> K>(&mut self, key: &F) {
+        for item in self.iter_mut() {
+            if let Item::Subfolder(f) = item {
+                f.children.sort_shortcuts_by_key(key);
+            }
+        }
+
+        sort_level_by_key(self, key);
+    }
+
+    fn sort_items_by_key<K: Ord, F: Fn(&Item) -> K>(&mut self, key: &F) {
+        self.sort_by_key(key);
+
+        for item in self.iter_mut() {
+            if let Item::Subfolder(f) = item {
+                f.children.sort_items_by_key(key);
+            }
+        }
+    }
+
+    fn children_at_path(&mut self, path: &[&str]) -> &mut Vec<Item> {
+        let (title, rest) = match path.split_first() {
+            Some(split) => split,
+            None => return self,
+        };
+
+        let position = self
+            .iter()
+            .position(|item| matches!(item, Item::Subfolder(f) if f.title == *title));
+
+        let index = position.unwrap_or_else(|| {
+            self.push(Item::Subfolder(Folder {
+                title: title.to_string(),
+                ..Folder::default()
+            }));
+            self.len() - 1
+        });
+
+        match &mut self[index] {
+            Item::Subfolder(f) => f.children.children_at_path(rest),
+            Item::Shortcut(_) => unreachable!("the path only matches subfolders"),
+        }
+    }
+
+    fn drain_shortcuts<F: Fn(&Bookmark) -> bool>(&mut self, predicate: &F) -> Vec<Bookmark> {
+        let mut drained = vec![];
+        let mut kept = vec![];
+
+        for item in self.drain(..) {
+            match item {
+                Item::Subfolder(mut f) => {
+                    drained.append(&mut f.children.drain_shortcuts(predicate));
+                    kept.push(Item::Subfolder(f));
+                }
+                Item::Shortcut(b) if predicate(&b) => drained.push(b),
+                item => kept.push(item),
+            }
+        }
+
+        *self = kept;
+        drained
+    }
+}
+
+/// Sorts the [Bookmark]s of a single level by the key, the [Folder]s keep their positions.
+pub(crate) fn sort_level_by_key<K: Ord, F: Fn(&Bookmark) -> K>(children: &mut [Item], key: &F) {
+    let mut bookmarks = vec![];
+
+    for item in children.iter_mut() {
+        if let Item::Shortcut(b) = item {
+            bookmarks.push(std::mem::take(b));
+        }
+    }
+
+    bookmarks.sort_by_key(key);
+    let mut sorted = bookmarks.into_iter();
+
+    for item in children.iter_mut() {
+        if let Item::Shortcut(b) = item {
+            if let Some(next) = sorted.next() {
+                *b = next;
+            }
+        }
+    }
+}
+
+/// Consumes nested items in the document order, every [Folder] being yielded before its children.
+///
+/// The children of the yielded folders are moved into the iterator. When `keep_shortcuts` is set,
+/// the folders keep their direct [Bookmark]s and only their subfolders are yielded separately.
+pub(crate) struct IntoNested {
+    stack: Vec<std::vec::IntoIter<Item>>,
+    keep_shortcuts: bool,
+}
+
+impl IntoNested {
+    pub(crate) fn new(children: Vec<Item>, keep_shortcuts: bool) -> Self {
+        IntoNested {
+            stack: vec![children.into_iter()],
+            keep_shortcuts,
+        }
+    }
+}
+
+impl Iterator for IntoNested {
+    type Item = Item;
+
+    fn next(&mut self) -> Option<Item> {
+        loop {
+            let item = match self.stack.last_mut()?.next() {
+                Some(item) => item,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            if let Item::Subfolder(mut f) = item {
+                let children = std::mem::take(&mut f.children);
+
+                if self.keep_shortcuts {
+                    let (subfolders, shortcuts) = children
+                        .into_iter()
+                        .partition(|child| matches!(child, Item::Subfolder(_)));
+
+                    f.children = shortcuts;
+                    self.stack.push(subfolders.into_iter());
+                } else {
+                    self.stack.push(children.into_iter());
+                }
+
+                return Some(Item::Subfolder(f));
+            }
+
+            return Some(item);
+        }
+    }
 }
 
 #[test]
@@ -78,4 +265,191 @@ fn should_get_all_nested_items() {
 
     assert_eq!(folder.children.shortcuts(), vec![&b0, &b1]);
     assert_eq!(folder.children.subfolders(), vec![&f0, &f1]);
+    assert_eq!(
+        folder.children.shortcuts_with_path(),
+        vec![(vec![], &b0), (vec!["", ""], &b1)]
+    );
+}
+
+#[test]
+fn should_get_all_nested_items_mutably() {
+    use crate::bookmark::BookmarkBuilder;
+    use crate::folder::FolderBuilder;
+
+    let b0 = BookmarkBuilder::default()
+        .href(String::from("test0"))
+        .title(String::from("test0"))
+        .build()
+        .unwrap();
+
+    let f0 = FolderBuilder::default()
+        .children(vec![Item::Shortcut(b0.clone())])
+        .build()
+        .unwrap();
+
+    let mut children = vec![Item::Shortcut(b0), Item::Subfolder(f0)];
+
+    for bookmark in children.shortcuts_mut() {
+        bookmark.title = String::from("edited");
+    }
+
+    let titles: Vec<&str> = children
+        .shortcuts()
+        .iter()
+        .map(|b| b.title.as_str())
+        .collect();
+    assert_eq!(titles, vec!["edited", "edited"]);
+}
+
+#[test]
+fn should_drain_matching_nested_items() {
+    use crate::bookmark::BookmarkBuilder;
+    use crate::folder::FolderBuilder;
+
+    let b0 = BookmarkBuilder::default()
+        .href(String::from("test0"))
+        .title(String::from("test0"))
+        .build()
+        .unwrap();
+
+    let b1 = BookmarkBuilder::default()
+        .href(String::from("test1"))
+        .title(String::from("test1"))
+        .build()
+        .unwrap();
+
+    let f0 = FolderBuilder::default()
+        .children(vec![Item::Shortcut(b0.clone()), Item::Shortcut(b1.clone())])
+        .build()
+        .unwrap();
+
+    let mut children = vec![Item::Shortcut(b0.clone()), Item::Subfolder(f0)];
+    let drained = children.drain_shortcuts(&|b| b.href == "test0");
+
+    assert_eq!(drained, vec![b0.clone(), b0]);
+    assert_eq!(children.shortcuts(), vec![&b1]);
+    assert_eq!(children.subfolders().len(), 1);
+}
+
+#[test]
+fn should_sort_nested_items_around_folders() {
+    use crate::bookmark::BookmarkBuilder;
+    use crate::folder::FolderBuilder;
+
+    let bookmark = |title: &str| {
+        BookmarkBuilder::default()
+            .href(title)
+            .title(title)
+            .build()
+            .unwrap()
+    };
+
+    let folder = FolderBuilder::default()
+        .children(vec![
+            Item::Shortcut(bookmark("d")),
+            Item::Shortcut(bookmark("c")),
+        ])
+        .build()
+        .unwrap();
+
+    let mut children = vec![
+        Item::Shortcut(bookmark("b")),
+        Item::Subfolder(folder),
+        Item::Shortcut(bookmark("a")),
+    ];
+    children.sort_shortcuts_by_key(&|b| b.title.clone());
+
+    let titles: Vec<&str> = children
+        .shortcuts()
+        .iter()
+        .map(|b| b.title.as_str())
+        .collect();
+
+    assert!(children[1].is_subfolder());
+    assert_eq!(titles, vec!["a", "c", "d", "b"]);
+}
+
+#[test]
+fn should_get_or_create_children_at_path() {
+    use crate::folder::FolderBuilder;
+
+    let existing = FolderBuilder::default().title("a").build().unwrap();
+    let mut children = vec![Item::Subfolder(existing)];
+
+    children
+        .children_at_path(&["a", "b"])
+        .push(Item::Subfolder(Folder::default()));
+
+    let titles: Vec<&str> = children
+        .subfolders()
+        .iter()
+        .map(|f| f.title.as_str())
+        .collect();
+
+    assert_eq!(children.len(), 1);
+    assert_eq!(titles, vec!["a", "b", ""]);
+}
+
+#[test]
+fn should_consume_nested_items_in_document_order() {
+    let children = || {
+        vec![
+            Item::Subfolder(Folder {
+                title: String::from("f1"),
+                children: vec![
+                    Item::Shortcut(Bookmark::from(("b1", "url"))),
+                    Item::Subfolder(Folder {
+                        title: String::from("f2"),
+                        children: vec![Item::Shortcut(Bookmark::from(("b2", "url")))],
+                        ..Folder::default()
+                    }),
+                ],
+                ..Folder::default()
+            }),
+            Item::Shortcut(Bookmark::from(("b3", "url"))),
+        ]
+    };
+
+    let titles: Vec<String> = IntoNested::new(children(), false)
+        .map(|item| match item {
+            Item::Subfolder(f) => format!("{}:{}", f.title, f.children.len()),
+            Item::Shortcut(b) => b.title,
+        })
+        .collect();
+
+    assert_eq!(titles, vec!["f1:0", "b1", "f2:0", "b2", "b3"]);
+
+    let titles: Vec<String> = IntoNested::new(children(), true)
+        .map(|item| match item {
+            Item::Subfolder(f) => format!("{}:{}", f.title, f.children.len()),
+            Item::Shortcut(b) => b.title,
+        })
+        .collect();
+
+    assert_eq!(titles, vec!["f1:1", "f2:1", "b3"]);
+}
+
+#[test]
+fn should_sort_nested_items_stably() {
+    let mut children = vec![
+        Item::Shortcut(Bookmark::from(("b", "first"))),
+        Item::Subfolder(Folder {
+            title: String::from("a"),
+            children: vec![
+                Item::Shortcut(Bookmark::from(("d", "url"))),
+                Item::Shortcut(Bookmark::from(("c", "url"))),
+            ],
+            ..Folder::default()
+        }),
+        Item::Shortcut(Bookmark::from(("b", "second"))),
+    ];
+
+    children.sort_items_by_key(&|item: &Item| item.title().to_string());
+
+    let titles: Vec<&str> = children.iter().map(Item::title).collect();
+    let nested: Vec<&str> = children[0].children().iter().map(Item::title).collect();
+
+    assert_eq!(titles, vec!["a", "b", "b"]);
+    assert_eq!(nested, vec!["c", "d"]);
+    assert_eq!(children[1].href(), Some("first"));
 }
@@ -0,0 +1,18 @@
+//! Contains the helpers that convert the UNIX time attributes into dates.
+use chrono::{DateTime, Utc};
+
+/// Parses a UNIX time attribute (in seconds), it fails on empty or malformed values.
+pub(crate) fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    value
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+}
+
+#[test]
+fn should_parse_unix_dates() {
+    assert_eq!(parse_date("1466009059").unwrap().timestamp(), 1466009059);
+    assert_eq!(parse_date(""), None);
+    assert_eq!(parse_date("date"), None);
+}
@@ -0,0 +1,169 @@
+//! Contains the helpers that describe the first difference between two trees of items.
+use crate::item::Item;
+use crate::Bookmark;
+use crate::Folder;
+
+/// Describes the difference of a field, the `path` locates the item that owns the field.
+pub(crate) fn field_difference(
+    path: &[String],
+    name: &str,
+    left: &str,
+    right: &str,
+) -> Option<String> {
+    if left == right {
+        None
+    } else {
+        Some(format!(
+            "/{}: `{}` differs ({:?} != {:?})",
+            path.join("/"),
+            name,
+            left,
+            right
+        ))
+    }
+}
+
+/// Describes the first difference between two lists of items, in the document order.
+pub(crate) fn first_difference(
+    left: &[Item],
+    right: &[Item],
+    path: &mut Vec<String>,
+) -> Option<String> {
+    for (index, (l, r)) in left.iter().zip(right.iter()).enumerate() {
+        path.push(index.to_string());
+
+        let difference = match (l, r) {
+            (Item::Subfolder(l), Item::Subfolder(r)) => folder_difference(l, r, path),
+            (Item::Shortcut(l), Item::Shortcut(r)) => bookmark_difference(l, r, path),
+            _ => Some(format!("/{}: the item kinds differ", path.join("/"))),
+        };
+
+        path.pop();
+
+        if difference.is_some() {
+            return difference;
+        }
+    }
+
+    if left.len() != right.len() {
+        Some(format!(
+            "/{}: the children counts differ ({} != {})",
+            path.join("/"),
+            left.len(),
+            right.len()
+        ))
+    } else {
+        None
+    }
+}
+
+fn folder_difference(left: &Folder, right: &Folder, path: &mut Vec<String>) -> Option<String> {
+    field_difference(path, "title", &left.title, &right.title)
+        .or_else(|| {
+            field_difference(
+                path,
+                "folded",
+                &left.folded.to_string(),
+                &right.folded.to_string(),
+            )
+        })
+        .or_else(|| field_difference(path, "add_date", &left.add_date, &right.add_date))
+        .or_else(|| {
+            field_difference(
+                path,
+                "last_modified",
+                &left.last_modified,
+                &right.last_modified,
+            )
+        })
+        .or_else(|| {
+            field_difference(
+                path,
+                "personal_toolbar_folder",
+                &left.personal_toolbar_folder.to_string(),
+                &right.personal_toolbar_folder.to_string(),
+            )
+        })
+        .or_else(|| {
+            field_difference(
+                path,
+                "unfiled_bookmarks_folder",
+                &left.unfiled_bookmarks_folder.to_string(),
+                &right.unfiled_bookmarks_folder.to_string(),
+            )
+        })
+        .or_else(|| {
+            field_difference(
+                path,
+                "extra_attributes",
+                &format!("{:?}", left.extra_attributes),
+                &format!("{:?}", right.extra_attributes),
+            )
+        })
+        .or_else(|| first_difference(&left.children, &right.children, path))
+}
+
+fn bookmark_difference(left: &Bookmark, right: &Bookmark, path: &[String]) -> Option<String> {
+    field_difference(path, "href", &left.href, &right.href)
+        .or_else(|| field_difference(path, "title", &left.title, &right.title))
+        .or_else(|| field_difference(path, "add_date", &left.add_date, &right.add_date))
+        .or_else(|| field_difference(path, "last_visit", &left.last_visit, &right.last_visit))
+        .or_else(|| {
+            field_difference(
+                path,
+                "last_modified",
+                &left.last_modified,
+                &right.last_modified,
+            )
+        })
+        .or_else(|| field_difference(path, "icon_uri", &left.icon_uri, &right.icon_uri))
+        .or_else(|| field_difference(path, "icon", &left.icon, &right.icon))
+        .or_else(|| field_difference(path, "target", &left.target, &right.target))
+        .or_else(|| field_difference(path, "rel", &left.rel, &right.rel))
+        .or_else(|| {
+            field_difference(
+                path,
+                "shortcut_url",
+                &left.shortcut_url,
+                &right.shortcut_url,
+            )
+        })
+        .or_else(|| field_difference(path, "description", &left.description, &right.description))
+}
+
+#[test]
+fn should_describe_first_difference() {
+    use crate::bookmark::BookmarkBuilder;
+    use crate::folder::FolderBuilder;
+
+    let bookmark = BookmarkBuilder::default()
+        .href("url")
+        .title("name")
+        .build()
+        .unwrap();
+    let mut renamed = bookmark.clone();
+    renamed.title = String::from("other");
+
+    let folder = |bookmark: Bookmark| {
+        Item::Subfolder(
+            FolderBuilder::default()
+                .title("folder")
+                .children(vec![Item::Shortcut(bookmark)])
+                .build()
+                .unwrap(),
+        )
+    };
+
+    let left = vec![folder(bookmark.clone())];
+    let right = vec![folder(renamed)];
+
+    assert_eq!(first_difference(&left, &left, &mut vec![]), None);
+    assert_eq!(
+        first_difference(&left, &right, &mut vec![]),
+        Some(String::from(r#"/0/0: `title` differs ("name" != "other")"#))
+    );
+    assert_eq!(
+        first_difference(&left, &[], &mut vec![]),
+        Some(String::from("/: the children counts differ (1 != 0)"))
+    );
+}
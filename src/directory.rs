@@ -0,0 +1,390 @@
+//! Contains the export and the import of the items as a tree of directories and files.
+//!
+//! Every folder is a directory, its attributes are stored in a `.folder.json` file.
+//! Every bookmark is a `<title>.json` file. The header of the document is stored in a `.netscape.json` file at the root.
+//! The `.order` file of every directory lists its entries, one per line, in the document order.
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+use crate::item::Item;
+use crate::{Bookmark, Folder, Netscape};
+
+/// The file that stores the header of the document at the root directory.
+pub(crate) const NETSCAPE_FILE: &str = ".netscape.json";
+
+/// The file that stores the attributes of a folder in its directory.
+pub(crate) const FOLDER_FILE: &str = ".folder.json";
+
+/// The file that lists the entries of a directory in the document order.
+pub(crate) const ORDER_FILE: &str = ".order";
+
+/// Writes the document into the `root` directory, it is created when missing.
+pub(crate) fn export_tree(netscape: &Netscape, root: &Path) -> Result<(), Error> {
+    fs::create_dir_all(root)?;
+
+    write_json(&root.join(NETSCAPE_FILE), &netscape.without_children())?;
+    export_children(&netscape.children, root)
+}
+
+fn export_children(children: &[Item], directory: &Path) -> Result<(), Error> {
+    let mut used = HashSet::new();
+    let names: Vec<String> = children
+        .iter()
+        .map(|item| match item {
+            Item::Subfolder(f) => unique_name(&sanitize_file_name(&f.title), "", &mut used),
+            Item::Shortcut(b) => unique_name(&sanitize_file_name(&b.title), ".json", &mut used),
+        })
+        .collect();
+
+    prune_entries(children, &names, directory)?;
+
+    let mut order = String::new();
+
+    for (item, name) in children.iter().zip(names) {
+        let path = directory.join(&name);
+
+        match item {
+            Item::Subfolder(f) => {
+                fs::create_dir_all(&path)?;
+
+                write_json(&path.join(FOLDER_FILE), &f.without_children())?;
+                export_children(&f.children, &path)?;
+            }
+            Item::Shortcut(b) => write_json(&path, b)?,
+        }
+
+        order.push_str(&name);
+        order.push('\n');
+    }
+
+    fs::write(directory.join(ORDER_FILE), order)
+}
+
+/// Removes the entries of a previous export that the `children` don't write again.
+///
+/// Only the entries listed by the previous `.order` file are removed, the other files of the directory are kept.
+/// An entry whose name is kept but that switches between a folder and a bookmark is removed too.
+fn prune_entries(children: &[Item], names: &[String], directory: &Path) -> Result<(), Error> {
+    let previous = match fs::read_to_string(directory.join(ORDER_FILE)) {
+        Ok(order) => order,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    for name in previous.lines().filter(|name| is_entry_name(name)) {
+        let path = directory.join(name);
+        let kept = names.iter().zip(children).any(|(kept, item)| {
+            kept == name && matches!(item, Item::Subfolder(_)) == path.is_dir()
+        });
+
+        if kept {
+            continue;
+        }
+
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else if path.is_file() {
+            fs::remove_file(&path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks if a line of an `.order` file names an entry of its own directory, not a hidden file or another path.
+fn is_entry_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\'])
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), Error> {
+    let mut json = serde_json::to_string_pretty(value)?;
+    json.push('\n');
+    fs::write(path, json)
+}
+
+/// Reads the document from the `root` directory, the files that are not bookmarks are ignored.
+pub(crate) fn import_tree(root: &Path) -> Result<Netscape, Error> {
+    let header = read_json(&root.join(NETSCAPE_FILE))?;
+    let title = string(&header, "title");
+
+    Ok(Netscape {
+        h1: header
+            .get("h1")
+            .and_then(Value::as_str)
+            .map(String::from)
+            .unwrap_or_else(|| title.clone()),
+        title,
+        add_date: string(&header, "add_date"),
+        last_modified: string(&header, "last_modified"),
+        charset: string(&header, "charset"),
+        metadata: serde_json::from_value(header.get("metadata").cloned().unwrap_or_default())
+            .unwrap_or_default(),
+        children: import_children(root)?,
+        source: None,
+    })
+}
+
+fn import_children(directory: &Path) -> Result<Vec<Item>, Error> {
+    let mut children = vec![];
+
+    for path in ordered_entries(directory)? {
+        if path.is_dir() {
+            let attributes = read_json(&path.join(FOLDER_FILE))?;
+            let mut title = string(&attributes, "title");
+
+            if attributes.get("title").is_none() {
+                title = file_name(&path);
+            }
+
+            children.push(Item::Subfolder(Folder {
+                title,
+                folded: boolean(&attributes, "folded"),
+                add_date: string(&attributes, "add_date"),
+                last_modified: string(&attributes, "last_modified"),
+                personal_toolbar_folder: boolean(&attributes, "personal_toolbar_folder"),
+                unfiled_bookmarks_folder: boolean(&attributes, "unfiled_bookmarks_folder"),
+                extra_attributes: extra_attributes(&attributes),
+                children: import_children(&path)?,
+            }));
+        } else if let Some(bookmark) = import_bookmark(&path)? {
+            children.push(Item::Shortcut(bookmark));
+        }
+    }
+
+    Ok(children)
+}
+
+/// Lists the existing entries of the `.order` file, or all the entries sorted by name when the directory has none.
+fn ordered_entries(directory: &Path) -> Result<Vec<PathBuf>, Error> {
+    let order = match fs::read_to_string(directory.join(ORDER_FILE)) {
+        Ok(order) => order,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return sorted_entries(directory),
+        Err(e) => return Err(e),
+    };
+
+    Ok(order
+        .lines()
+        .filter(|name| is_entry_name(name))
+        .map(|name| directory.join(name))
+        .filter(|path| path.exists())
+        .collect())
+}
+
+/// Lists the entries of the directory sorted by name, the hidden files are skipped.
+fn sorted_entries(directory: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut entries = vec![];
+
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+
+        if !file_name(&path).starts_with('.') {
+            entries.push(path);
+        }
+    }
+
+    entries.sort();
+
+    Ok(entries)
+}
+
+fn import_bookmark(path: &Path) -> Result<Option<Bookmark>, Error> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+
+    let bookmark = match extension.as_deref() {
+        Some("json") => {
+            let value = read_json(path)?;
+
+            value.get("href").map(|_| Bookmark {
+                href: string(&value, "href"),
+                title: string(&value, "title"),
+                add_date: string(&value, "add_date"),
+                last_visit: string(&value, "last_visit"),
+                last_modified: string(&value, "last_modified"),
+                icon_uri: string(&value, "icon_uri"),
+                icon: string(&value, "icon"),
+                target: string(&value, "target"),
+                rel: string(&value, "rel"),
+                shortcut_url: string(&value, "shortcut_url"),
+                description: string(&value, "description"),
+            })
+        }
+        Some("url") => fs::read_to_string(path)?
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("URL="))
+            .map(|href| Bookmark {
+                href: href.to_string(),
+                title: file_stem(path),
+                ..Bookmark::default()
+            }),
+        _ => None,
+    };
+
+    Ok(bookmark)
+}
+
+/// Reads a JSON file, a missing file is read as an empty object.
+fn read_json(path: &Path) -> Result<Value, Error> {
+    match fs::read_to_string(path) {
+        Ok(json) => Ok(serde_json::from_str(&json)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Value::Null),
+        Err(e) => Err(e),
+    }
+}
+
+fn string(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn boolean(value: &Value, key: &str) -> bool {
+    value.get(key).and_then(Value::as_bool).unwrap_or_default()
+}
+
+fn extra_attributes(value: &Value) -> Vec<(String, String)> {
+    serde_json::from_value(value.get("extra_attributes").cloned().unwrap_or_default())
+        .unwrap_or_default()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Replaces the characters that are forbidden in the file names of the common file systems.
+pub(crate) fn sanitize_file_name(title: &str) -> String {
+    let replaced: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    let trimmed = replaced.trim().trim_matches('.');
+
+    if trimmed.is_empty() {
+        String::from("untitled")
+    } else {
+        trimmed.chars().take(100).collect()
+    }
+}
+
+/// Disambiguates a file name with a ` (n)` suffix, the comparison ignores the case.
+fn unique_name(base: &str, extension: &str, used: &mut HashSet<String>) -> String {
+    let mut name = format!("{}{}", base, extension);
+    let mut index = 2;
+
+    while !used.insert(name.to_lowercase()) {
+        name = format!("{} ({}){}", base, index, extension);
+        index += 1;
+    }
+
+    name
+}
+
+#[test]
+fn should_import_url_files_and_ignore_others() {
+    let root = std::env::temp_dir().join("bookmarkt_import_url_files");
+    let _ = fs::remove_dir_all(&root);
+
+    fs::create_dir_all(root.join("Work")).unwrap();
+    fs::write(
+        root.join("Work").join("Rust.url"),
+        "[InternetShortcut]\nURL=https://www.rust-lang.org/\n",
+    )
+    .unwrap();
+    fs::write(root.join("notes.txt"), "ignored").unwrap();
+
+    let netscape = import_tree(&root).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+
+    match &netscape.children[..] {
+        [Item::Subfolder(work)] => {
+            assert_eq!(work.title, "Work");
+            assert!(
+                matches!(&work.children[..], [Item::Shortcut(b)] if b.title == "Rust" && b.href == "https://www.rust-lang.org/")
+            );
+        }
+        _ => panic!("unexpected children"),
+    }
+}
+
+#[test]
+fn should_sanitize_file_names() {
+    assert_eq!(sanitize_file_name("a/b: c?"), "a_b_ c_");
+    assert_eq!(sanitize_file_name(" .. "), "untitled");
+    assert_eq!(sanitize_file_name("..hidden"), "hidden");
+}
+
+#[test]
+fn should_disambiguate_file_names() {
+    let mut used = HashSet::new();
+
+    assert_eq!(unique_name("Work", "", &mut used), "Work");
+    assert_eq!(unique_name("work", "", &mut used), "work (2)");
+    assert_eq!(unique_name("Work", ".json", &mut used), "Work.json");
+    assert_eq!(unique_name("Work", ".json", &mut used), "Work (2).json");
+}
+
+#[test]
+fn should_prune_removed_entries_on_export() {
+    let root = std::env::temp_dir().join("bookmarkt_prune_removed_entries");
+    let _ = fs::remove_dir_all(&root);
+
+    let links = (1..=6).map(|i| (format!("Link {}", i), format!("https://example.com/{}", i)));
+    let mut netscape = Netscape::from_links("Links", links);
+    export_tree(&netscape, &root).unwrap();
+    fs::write(root.join("notes.txt"), "kept").unwrap();
+
+    netscape.children.remove(2);
+    export_tree(&netscape, &root).unwrap();
+
+    let imported = import_tree(&root).unwrap();
+    let notes = root.join("notes.txt").is_file();
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(imported.get_bookmarks().len(), 5);
+    assert_eq!(imported, netscape);
+    assert!(notes);
+}
+
+#[test]
+fn should_import_only_ordered_entries() {
+    let root = std::env::temp_dir().join("bookmarkt_import_ordered_entries");
+    let _ = fs::remove_dir_all(&root);
+
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join("b.url"), "URL=https://b.example/\n").unwrap();
+    fs::write(root.join("a.url"), "URL=https://a.example/\n").unwrap();
+    fs::write(root.join("stale.url"), "URL=https://stale.example/\n").unwrap();
+    fs::write(root.join(ORDER_FILE), "b.url\na.url\nmissing.url\n").unwrap();
+
+    let netscape = import_tree(&root).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+
+    let hrefs: Vec<&str> = netscape
+        .get_bookmarks()
+        .iter()
+        .map(|b| b.href.as_str())
+        .collect();
+
+    assert_eq!(hrefs, vec!["https://b.example/", "https://a.example/"]);
+}
@@ -0,0 +1,90 @@
+//! Contains the export of the items as a GraphViz `digraph`, for the visualizations.
+use crate::item::Item;
+use crate::Netscape;
+
+/// Renders the document as a `digraph`, the folders and the bookmarks are nodes linked to their parent folder.
+pub(crate) fn to_dot(netscape: &Netscape) -> String {
+    let mut dot = String::from("digraph bookmarks {\n");
+
+    dot.push_str(&format!(
+        "    n0 [shape=folder, label=\"{}\"];\n",
+        escape_dot(&netscape.title)
+    ));
+
+    let mut count = 0;
+    write_children(&netscape.children, 0, &mut count, &mut dot);
+
+    dot.push_str("}\n");
+    dot
+}
+
+fn write_children(children: &[Item], parent: usize, count: &mut usize, dot: &mut String) {
+    for child in children {
+        *count += 1;
+        let id = *count;
+
+        match child {
+            Item::Subfolder(f) => {
+                dot.push_str(&format!(
+                    "    n{} [shape=folder, label=\"{}\"];\n",
+                    id,
+                    escape_dot(&f.title)
+                ));
+                dot.push_str(&format!("    n{} -> n{};\n", parent, id));
+                write_children(&f.children, id, count, dot);
+            }
+            Item::Shortcut(b) => {
+                dot.push_str(&format!(
+                    "    n{} [shape=note, label=\"{}\", URL=\"{}\"];\n",
+                    id,
+                    escape_dot(&b.title),
+                    escape_dot(&b.href)
+                ));
+                dot.push_str(&format!("    n{} -> n{};\n", parent, id));
+            }
+        }
+    }
+}
+
+/// Escapes a DOT quoted string, the backslashes, the quotes and the line breaks.
+fn escape_dot(raw: &str) -> String {
+    raw.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\r', "")
+        .replace('\n', "\\n")
+}
+
+#[test]
+fn should_escape_dot_labels() {
+    assert_eq!(escape_dot("a \"b\" \\ c\nd"), "a \\\"b\\\" \\\\ c\\nd");
+}
+
+#[test]
+fn should_render_nested_digraph() {
+    use crate::{Bookmark, Folder};
+
+    let netscape = Netscape {
+        title: String::from("Bookmarks"),
+        children: vec![Item::Subfolder(Folder {
+            title: String::from("Work"),
+            children: vec![Item::Shortcut(Bookmark::from((
+                "Kernel",
+                "https://www.kernel.org/",
+            )))],
+            ..Folder::default()
+        })],
+        ..Netscape::default()
+    };
+
+    assert_eq!(
+        to_dot(&netscape),
+        r#"digraph bookmarks {
+    n0 [shape=folder, label="Bookmarks"];
+    n1 [shape=folder, label="Work"];
+    n0 -> n1;
+    n2 [shape=note, label="Kernel", URL="https://www.kernel.org/"];
+    n1 -> n2;
+}
+"#
+    );
+}
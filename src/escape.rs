@@ -0,0 +1,242 @@
+//! Contains the helpers that escape the text of the items for the HTML reports and the rendered documents.
+#[cfg(feature = "render")]
+use crate::item::Item;
+#[cfg(feature = "render")]
+use crate::render::{EscapePolicy, VALUE_ESCAPE};
+#[cfg(feature = "render")]
+use crate::{Bookmark, Folder, Netscape};
+
+/// Escapes the HTML special characters, the result is safe in a text node and in a quoted attribute.
+pub(crate) fn escape_html(raw: &str) -> String {
+    let mut escaped = String::with_capacity(raw.len());
+
+    for c in raw.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Escapes the characters that Firefox escapes in the text of its exports, i.e. `&`, `<`, `>` and `"`.
+#[cfg(feature = "render")]
+fn escape_markup(raw: &str) -> String {
+    let mut escaped = String::with_capacity(raw.len());
+
+    for c in raw.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Escapes the quotes of an attribute value, and the `&` that would be read as a character reference,
+/// e.g. in `&amp;` or `&#39;`. The other `&`, e.g. the separators of the query strings, are kept.
+pub(crate) fn escape_quotes(raw: &str) -> String {
+    let mut escaped = String::with_capacity(raw.len());
+
+    for (index, c) in raw.char_indices() {
+        match c {
+            '"' => escaped.push_str("&quot;"),
+            '&' if is_reference(&raw[index + 1..]) => escaped.push_str("&amp;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Checks if the text following a `&` reads as a character reference, i.e. `#…` or `name;`.
+fn is_reference(rest: &str) -> bool {
+    let name = rest
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .map_or(rest, |end| &rest[..end]);
+
+    rest.starts_with('#') || (!name.is_empty() && rest[name.len()..].starts_with(';'))
+}
+
+/// Copies the document with its text and its attribute values escaped according to the [EscapePolicy],
+/// their newlines and the [VALUE_ESCAPE] characters are escaped by the [VALUE_ESCAPE] character.
+#[cfg(feature = "render")]
+pub(crate) fn escape_document(netscape: &Netscape, policy: EscapePolicy) -> Netscape {
+    let escaper = Escaper::new(policy);
+
+    Netscape {
+        title: escaper.text(&netscape.title),
+        h1: escaper.text(&netscape.h1),
+        add_date: escaper.attribute(&netscape.add_date),
+        last_modified: escaper.attribute(&netscape.last_modified),
+        charset: escaper.attribute(&netscape.charset),
+        metadata: netscape
+            .metadata
+            .iter()
+            .map(|(key, value)| (Escaper::value(key.clone()), Escaper::value(value.clone())))
+            .collect(),
+        children: escaper.children(&netscape.children),
+        source: None,
+    }
+}
+
+/// Copies an item without its children, escaped like the items of [escape_document].
+#[cfg(feature = "render")]
+pub(crate) fn escape_shell(item: &Item, policy: EscapePolicy) -> Item {
+    let escaper = Escaper::new(policy);
+
+    match item {
+        Item::Subfolder(f) => escaper.item(&Item::Subfolder(f.without_children())),
+        Item::Shortcut(b) => Item::Shortcut(escaper.bookmark(b)),
+    }
+}
+
+/// Copies a bookmark escaped like the items of [escape_document].
+#[cfg(feature = "render")]
+pub(crate) fn escape_bookmark(bookmark: &Bookmark, policy: EscapePolicy) -> Bookmark {
+    Escaper::new(policy).bookmark(bookmark)
+}
+
+#[cfg(feature = "render")]
+struct Escaper {
+    policy: EscapePolicy,
+}
+
+#[cfg(feature = "render")]
+impl Escaper {
+    fn new(policy: EscapePolicy) -> Self {
+        Escaper { policy }
+    }
+
+    fn text(&self, raw: &str) -> String {
+        Escaper::value(match self.policy {
+            EscapePolicy::Html => escape_markup(raw),
+            _ => raw.to_string(),
+        })
+    }
+
+    /// Escapes an attribute value, the [EscapePolicy::Html] only escapes its quotes like the browsers,
+    /// e.g. the `&` of the query strings are kept.
+    fn attribute(&self, raw: &str) -> String {
+        Escaper::value(match self.policy {
+            EscapePolicy::None => raw.to_string(),
+            EscapePolicy::Html => escape_quotes(raw),
+            EscapePolicy::Attribute => escape_markup(raw),
+        })
+    }
+
+    fn value(value: String) -> String {
+        if !value.contains(['\n', VALUE_ESCAPE]) {
+            return value;
+        }
+
+        let mut escaped = String::with_capacity(value.len() + 3);
+
+        for c in value.chars() {
+            match c {
+                '\n' => escaped.extend([VALUE_ESCAPE, 'n']),
+                VALUE_ESCAPE => escaped.extend([VALUE_ESCAPE, VALUE_ESCAPE]),
+                c => escaped.push(c),
+            }
+        }
+
+        escaped
+    }
+
+    fn children(&self, children: &[Item]) -> Vec<Item> {
+        children.iter().map(|child| self.item(child)).collect()
+    }
+
+    fn item(&self, item: &Item) -> Item {
+        match item {
+            Item::Subfolder(f) => Item::Subfolder(Folder {
+                title: self.text(&f.title),
+                folded: f.folded,
+                add_date: self.attribute(&f.add_date),
+                last_modified: self.attribute(&f.last_modified),
+                personal_toolbar_folder: f.personal_toolbar_folder,
+                unfiled_bookmarks_folder: f.unfiled_bookmarks_folder,
+                extra_attributes: f
+                    .extra_attributes
+                    .iter()
+                    .map(|(name, value)| (name.clone(), self.attribute(value)))
+                    .collect(),
+                children: self.children(&f.children),
+            }),
+            Item::Shortcut(b) => Item::Shortcut(self.bookmark(b)),
+        }
+    }
+
+    fn bookmark(&self, b: &Bookmark) -> Bookmark {
+        Bookmark {
+            href: self.attribute(&b.href),
+            title: self.text(&b.title),
+            add_date: self.attribute(&b.add_date),
+            last_visit: self.attribute(&b.last_visit),
+            last_modified: self.attribute(&b.last_modified),
+            icon_uri: self.attribute(&b.icon_uri),
+            icon: self.attribute(&b.icon),
+            target: self.attribute(&b.target),
+            rel: self.attribute(&b.rel),
+            shortcut_url: self.attribute(&b.shortcut_url),
+            description: self.text(&b.description),
+        }
+    }
+}
+
+#[test]
+fn should_escape_html() {
+    assert_eq!(
+        escape_html(r#"<a href="x">Tom & 'Jerry'</a>"#),
+        "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;"
+    );
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_escape_according_to_policy() {
+    let netscape = Netscape {
+        title: String::from("Tom & Jerry"),
+        children: vec![Item::Shortcut(Bookmark::from((
+            "<b>",
+            "https://a.b/?x=1&y=2",
+        )))],
+        ..Netscape::default()
+    };
+
+    let html = escape_document(&netscape, EscapePolicy::Html);
+    let attribute = escape_document(&netscape, EscapePolicy::Attribute);
+    let none = escape_document(&netscape, EscapePolicy::None);
+
+    assert_eq!(html.title, "Tom &amp; Jerry");
+    assert_eq!(attribute.title, "Tom & Jerry");
+    assert_eq!(none, netscape);
+
+    match (&html.children[0], &attribute.children[0]) {
+        (Item::Shortcut(h), Item::Shortcut(a)) => {
+            assert_eq!(h.title, "&lt;b&gt;");
+            assert_eq!(h.href, "https://a.b/?x=1&y=2");
+            assert_eq!(a.title, "<b>");
+            assert_eq!(a.href, "https://a.b/?x=1&amp;y=2");
+        }
+        _ => panic!("unexpected children"),
+    }
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_escape_quotes_and_references() {
+    assert_eq!(
+        escape_quotes(r#"?q="a"&b=1&amp;&#39;&c"#),
+        "?q=&quot;a&quot;&b=1&amp;amp;&amp;#39;&c"
+    );
+}
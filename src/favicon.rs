@@ -0,0 +1,137 @@
+//! Contains the helpers that locate and fetch the favicons of the bookmarks.
+use url::Url;
+
+/// Computes the `scheme://host/favicon.ico` url of the domain of a `href`.
+/// It fails on malformed urls and on urls without host.
+pub(crate) fn favicon_url(href: &str) -> Option<String> {
+    let url = Url::parse(href).ok()?;
+    let host = url.host_str()?;
+
+    match url.port() {
+        Some(port) => Some(format!("{}://{}:{}/favicon.ico", url.scheme(), host, port)),
+        None => Some(format!("{}://{}/favicon.ico", url.scheme(), host)),
+    }
+}
+
+/// Stores the options available to fetch the favicons of a [Netscape] document.
+///
+/// [Netscape]: crate::Netscape
+#[cfg(feature = "online")]
+#[derive(Clone, Debug)]
+pub struct FaviconOptions {
+    /// The `concurrency` attribute is the maximum number of simultaneous requests.
+    pub concurrency: usize,
+
+    /// The `timeout` attribute is the maximum duration of a request.
+    pub timeout: std::time::Duration,
+}
+
+#[cfg(feature = "online")]
+impl Default for FaviconOptions {
+    fn default() -> Self {
+        FaviconOptions {
+            concurrency: 8,
+            timeout: std::time::Duration::from_secs(10),
+        }
+    }
+}
+
+/// Fetches a favicon and encodes it as a `data:` URI, the failures are ignored.
+#[cfg(feature = "online")]
+pub(crate) async fn fetch_data_uri(client: &reqwest::Client, url: &str) -> Option<String> {
+    use base64::Engine;
+
+    let response = client.get(url).send().await.ok()?;
+
+    if !response.status().is_success() {
+        return None;
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| value.starts_with("image/"))
+        .unwrap_or("image/x-icon")
+        .to_string();
+
+    let bytes = response.bytes().await.ok()?;
+
+    if bytes.is_empty() {
+        None
+    } else {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&bytes);
+        Some(format!("data:{};base64,{}", content_type, encoded))
+    }
+}
+
+#[test]
+fn should_compute_favicon_url() {
+    assert_eq!(
+        favicon_url("https://www.kernel.org/pub/linux/").unwrap(),
+        "https://www.kernel.org/favicon.ico"
+    );
+    assert_eq!(
+        favicon_url("http://localhost:8080/index.html").unwrap(),
+        "http://localhost:8080/favicon.ico"
+    );
+    assert_eq!(favicon_url("mailto:someone@example.com"), None);
+    assert_eq!(favicon_url("kernel"), None);
+}
+
+#[cfg(feature = "online")]
+#[test]
+fn should_fetch_favicons_from_local_servers() {
+    use crate::Netscape;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let serve = |response: &'static str| {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        format!("http://127.0.0.1:{}/page", port)
+    };
+
+    let found = serve("HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nContent-Length: 4\r\nConnection: close\r\n\r\nicon");
+    let missing = serve("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+    let empty = serve("HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+    let closed = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!(
+            "http://127.0.0.1:{}/page",
+            listener.local_addr().unwrap().port()
+        )
+    };
+
+    let links = vec![found, missing, empty, closed]
+        .into_iter()
+        .map(|href| (href.clone(), href));
+    let mut netscape = Netscape::from_links("Links", links);
+    let options = FaviconOptions {
+        timeout: std::time::Duration::from_secs(5),
+        ..FaviconOptions::default()
+    };
+
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(netscape.fetch_favicons_async(&options));
+
+    let icons: Vec<&str> = netscape
+        .get_bookmarks()
+        .iter()
+        .map(|b| b.icon.as_str())
+        .collect();
+
+    assert_eq!(icons, vec!["data:image/png;base64,aWNvbg==", "", "", ""]);
+}
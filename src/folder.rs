@@ -1,17 +1,31 @@
 //! Contains the [Folder] model and its associated tests.
+#[cfg(feature = "render")]
 use askama::Template;
 use kuchiki::NodeRef;
 use serde::Serialize;
 
 use crate::item::Item;
 use crate::node_ref_ext::*;
+use crate::parse::ParseOptions;
+use crate::Bookmark;
+
+/// The attributes of the `<H3/>` tag that are stored in dedicated fields.
+const KNOWN_ATTRIBUTES: [&str; 5] = [
+    "FOLDED",
+    "ADD_DATE",
+    "LAST_MODIFIED",
+    "PERSONAL_TOOLBAR_FOLDER",
+    "UNFILED_BOOKMARKS_FOLDER",
+];
 
 /// Parses the item formated as a `subfolder`. By nature, the [Folder] are nested structures.
-#[derive(Serialize, Clone, Builder, Debug, Default, Template)]
-#[template(path = "folder.j2", escape = "none")]
+#[derive(Serialize, Clone, Builder, Debug, Default)]
+#[cfg_attr(feature = "render", derive(Template))]
+#[cfg_attr(feature = "render", template(path = "folder.j2", escape = "none"))]
 #[builder(setter(into))]
 pub struct Folder {
     /// The `title` represents the subfolder's title, it is usually encapsulated by an `<H3/>` tag.
+    /// The inline markup of the `<H3/>` content is stripped, only its text is kept.
     #[builder(default)]
     pub title: String,
 
@@ -35,6 +49,13 @@ pub struct Folder {
     #[builder(default = "false")]
     pub unfiled_bookmarks_folder: bool,
 
+    /// The `extra_attributes` stores the unknown attributes of the `<H3/>` tag as `(name, value)` pairs,
+    /// they are rendered after the known attributes.
+    /// The names are upper-cased and sorted: the parser stores the attributes in a sorted map,
+    /// so the source order of the attributes is lost.
+    #[builder(default)]
+    pub extra_attributes: Vec<(String, String)>,
+
     /// In contrast to the other items, a [Folder] has a `children` attribute that contains all its nested items.
     /// The `children` [Vec] stores all the subfolder's items in the **same** order than the initial bookmarks organization.
     #[builder(default)]
@@ -44,13 +65,27 @@ pub struct Folder {
 impl Folder {
     /// Creates a [Folder] model from a parsed HTML DOM.
     pub fn from_node(node: &NodeRef) -> Option<Self> {
+        Folder::from_node_with(node, &ParseOptions::default())
+    }
+
+    /// Creates a [Folder] model from a parsed HTML DOM, customized by the [ParseOptions].
+    pub fn from_node_with(node: &NodeRef, options: &ParseOptions) -> Option<Self> {
+        Folder::from_node_observed(node, options, &mut || {})
+    }
+
+    /// Creates a [Folder] model from a parsed HTML DOM, `on_item` is called after every parsed nested item.
+    pub(crate) fn from_node_observed(
+        node: &NodeRef,
+        options: &ParseOptions,
+        on_item: &mut dyn FnMut(),
+    ) -> Option<Self> {
         let mut folder = None;
 
         if node.is_element("DT") {
             let h3 = node.children().find(|n| n.is_element("H3"));
 
             if let Some(node) = h3 {
-                folder = Folder::from_node(&node);
+                folder = Folder::from_node_observed(&node, options, on_item);
             }
         } else if node.is_element("H3") {
             let mut builder = FolderBuilder::default();
@@ -59,11 +94,11 @@ impl Folder {
                 builder.folded(true);
             }
 
-            if let Some(attribute) = node.select_attribute("ADD_DATE") {
+            if let Some(attribute) = select_date_attribute(node, "ADD_DATE") {
                 builder.add_date(attribute.value);
             }
 
-            if let Some(attribute) = node.select_attribute("LAST_MODIFIED") {
+            if let Some(attribute) = select_date_attribute(node, "LAST_MODIFIED") {
                 builder.last_modified(attribute.value);
             }
 
@@ -75,20 +110,35 @@ impl Folder {
                 builder.unfiled_bookmarks_folder(true);
             }
 
+            let extra_attributes: Vec<(String, String)> = node
+                .attribute_pairs()
+                .into_iter()
+                .filter(|(name, _)| !KNOWN_ATTRIBUTES.contains(&name.as_str()))
+                .collect();
+
+            builder.extra_attributes(extra_attributes);
             builder.title(node.text_contents());
 
-            for sibling in node.following_siblings() {
-                if sibling.is_element("DL") {
-                    let mut children = vec![];
+            let mut list = node.following_siblings().find(|n| n.is_element("DL"));
 
-                    for child in sibling.children() {
-                        if let Some(item) = Item::from_node(&child) {
-                            children.push(item)
-                        }
-                    }
+            if list.is_none() {
+                list = node
+                    .parent()
+                    .filter(|parent| parent.is_element("DT"))
+                    .and_then(|dt| following_list(&dt));
+            }
+
+            if let Some(list) = list {
+                let mut children = vec![];
 
-                    builder.children(children);
+                for child in list.children() {
+                    if let Some(item) = Item::from_node_observed(&child, options, on_item) {
+                        children.push(item);
+                        on_item();
+                    }
                 }
+
+                builder.children(children);
             }
 
             if let Ok(built) = builder.build() {
@@ -98,6 +148,86 @@ impl Folder {
 
         folder
     }
+
+    /// Splits the direct children of the [Folder] into its subfolders and its bookmarks, in the document order.
+    /// The nested items aren't visited.
+    pub fn partition(&self) -> (Vec<&Folder>, Vec<&Bookmark>) {
+        let mut folders = vec![];
+        let mut bookmarks = vec![];
+
+        for child in &self.children {
+            match child {
+                Item::Subfolder(folder) => folders.push(folder),
+                Item::Shortcut(bookmark) => bookmarks.push(bookmark),
+            }
+        }
+
+        (folders, bookmarks)
+    }
+
+    /// Copies the attributes of the [Folder], without its children.
+    pub(crate) fn without_children(&self) -> Self {
+        Folder {
+            title: self.title.clone(),
+            folded: self.folded,
+            add_date: self.add_date.clone(),
+            last_modified: self.last_modified.clone(),
+            personal_toolbar_folder: self.personal_toolbar_folder,
+            unfiled_bookmarks_folder: self.unfiled_bookmarks_folder,
+            extra_attributes: self.extra_attributes.clone(),
+            children: vec![],
+        }
+    }
+}
+
+/// Finds the `<DL/>` of a folder whose `<DT/>` was closed before its list.
+///
+/// It happens with an explicit `</DT>` tag, or with a `<DD/>` description between the `<H3/>` and the `<DL/>` :
+/// the `<DD/>` closes the `<DT/>` and the list ends up nested in the description.
+fn following_list(dt: &NodeRef) -> Option<NodeRef> {
+    for sibling in dt.following_siblings() {
+        if sibling.is_element("DL") {
+            return Some(sibling);
+        } else if sibling.is_element("DD") {
+            return sibling.children().find(|n| n.is_element("DL"));
+        } else if sibling.as_element().is_some() && !sibling.is_element("P") {
+            return None;
+        }
+    }
+
+    None
+}
+
+impl FolderBuilder {
+    /// Appends the [Bookmark]s to the children of the folder, they are wrapped as shortcut items.
+    ///
+    /// ```rust
+    /// use bookmarkt::{Bookmark, FolderBuilder};
+    ///
+    /// let folder = FolderBuilder::default()
+    ///     .title("Linux")
+    ///     .bookmarks(vec![Bookmark::from(("Kernel", "https://www.kernel.org/"))])
+    ///     .subfolders(vec![FolderBuilder::default().title("Distributions").build().unwrap()])
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert!(folder.children[0].is_shortcut());
+    /// assert!(folder.children[1].is_subfolder());
+    /// ```
+    pub fn bookmarks(&mut self, bookmarks: Vec<Bookmark>) -> &mut Self {
+        self.children
+            .get_or_insert_with(Vec::new)
+            .extend(bookmarks.into_iter().map(Item::Shortcut));
+        self
+    }
+
+    /// Appends the [Folder]s to the children of the folder, they are wrapped as subfolder items.
+    pub fn subfolders(&mut self, subfolders: Vec<Folder>) -> &mut Self {
+        self.children
+            .get_or_insert_with(Vec::new)
+            .extend(subfolders.into_iter().map(Item::Subfolder));
+        self
+    }
 }
 
 impl PartialEq for Folder {
@@ -108,6 +238,7 @@ impl PartialEq for Folder {
     }
 }
 
+#[cfg(feature = "render")]
 #[test]
 fn should_render_folder_html() {
     let rendered = r#"<DT><H3 FOLDED ADD_DATE="date" LAST_MODIFIED="date">name</H3>
@@ -120,6 +251,7 @@ fn should_render_folder_html() {
         unfiled_bookmarks_folder: false,
         last_modified: String::from("date"),
         add_date: String::from("date"),
+        extra_attributes: vec![],
         children: vec![],
     };
 
@@ -138,7 +270,7 @@ fn should_parse_netscape_empty_folder() {
     let h3 = parse_html().one(item).select_first("H3").unwrap();
 
     assert_eq!(
-        Folder::from_node(&h3.as_node()).unwrap(),
+        Folder::from_node(h3.as_node()).unwrap(),
         Folder {
             title: String::from("title"),
             folded: true,
@@ -146,6 +278,7 @@ fn should_parse_netscape_empty_folder() {
             unfiled_bookmarks_folder: false,
             last_modified: String::from("date"),
             add_date: String::from("date"),
+            extra_attributes: vec![],
             children: vec![]
         }
     )
@@ -194,7 +327,7 @@ fn should_parse_netscape_nested_folders() {
     );
 
     assert_eq!(
-        Folder::from_node(&dt.as_node()).unwrap(),
+        Folder::from_node(dt.as_node()).unwrap(),
         FolderBuilder::default()
             .title("nested0")
             .children(vec![n1])
@@ -205,7 +338,7 @@ fn should_parse_netscape_nested_folders() {
 
 #[test]
 fn should_serialize_json_folder() {
-    let json = r#"{"title":"title","folded":false,"add_date":"date","last_modified":"date","personal_toolbar_folder":true,"unfiled_bookmarks_folder":false,"children":[]}"#;
+    let json = r#"{"title":"title","folded":false,"add_date":"date","last_modified":"date","personal_toolbar_folder":true,"unfiled_bookmarks_folder":false,"extra_attributes":[],"children":[]}"#;
     let folder = Folder {
         title: String::from("title"),
         folded: false,
@@ -213,8 +346,179 @@ fn should_serialize_json_folder() {
         unfiled_bookmarks_folder: false,
         add_date: String::from("date"),
         last_modified: String::from("date"),
+        extra_attributes: vec![],
         children: vec![],
     };
 
     assert_eq!(serde_json::to_string(&folder).unwrap(), json)
 }
+
+#[cfg(feature = "render")]
+#[test]
+fn should_roundtrip_folder_extra_attributes() {
+    use kuchiki::parse_html;
+    use kuchiki::traits::TendrilSink;
+
+    let item = r#"
+    <DT><H3 ADD_DATE="date" DATA-ID="42" SYNC="on">title</H3>
+    <DL><p>
+    </DL><p>"#;
+    let h3 = parse_html().one(item).select_first("H3").unwrap();
+    let folder = Folder::from_node(h3.as_node()).unwrap();
+
+    assert_eq!(
+        folder.extra_attributes,
+        vec![
+            (String::from("DATA-ID"), String::from("42")),
+            (String::from("SYNC"), String::from("on")),
+        ]
+    );
+    assert_eq!(
+        folder.render().unwrap(),
+        r#"<DT><H3 ADD_DATE="date" DATA-ID="42" SYNC="on">title</H3>
+<DL><p>
+</DL><p>"#
+    );
+
+    let raw =
+        r#"<DL><p><DT><H3 sync='on' ADD_DATE="date" Data-Id=42>title</H3><DL><p></DL><p></DL><p>"#;
+    let netscape = crate::Netscape::from_html(raw).unwrap();
+
+    assert_eq!(
+        netscape.get_folders()[0].extra_attributes,
+        vec![
+            (String::from("DATA-ID"), String::from("42")),
+            (String::from("SYNC"), String::from("on")),
+        ]
+    );
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_strip_folder_title_markup() {
+    use kuchiki::parse_html;
+    use kuchiki::traits::TendrilSink;
+
+    let item = r#"
+    <DT><H3><b>Work</b> stuff</H3>
+    <DL><p>
+    </DL><p>"#;
+    let h3 = parse_html().one(item).select_first("H3").unwrap();
+    let folder = Folder::from_node(h3.as_node()).unwrap();
+
+    assert_eq!(folder.title, "Work stuff");
+    assert!(folder.render().unwrap().contains("<H3>Work stuff</H3>"));
+}
+
+#[test]
+fn should_parse_netscape_folder_closed_before_list() {
+    use kuchiki::parse_html;
+    use kuchiki::traits::TendrilSink;
+
+    let closed = r#"
+    <DL><p>
+    <DT><H3>closed</H3></DT>
+    <DL><p>
+    <DT><A HREF="url">name</A>
+    </DL><p>
+    </DL>"#;
+
+    let described = r#"
+    <DL><p>
+    <DT><H3>described</H3>
+    <DD>description
+    <DL><p>
+    <DT><A HREF="url">name</A>
+    </DL><p>
+    </DL>"#;
+
+    for item in &[closed, described] {
+        let document = parse_html().one(*item);
+        let h3 = document.select_first("H3").unwrap();
+        let folder = Folder::from_node(h3.as_node()).unwrap();
+
+        assert_eq!(folder.children.len(), 1);
+        assert!(folder.children[0].is_shortcut());
+    }
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_render_folder_dates_only_when_set() {
+    use kuchiki::parse_html;
+    use kuchiki::traits::TendrilSink;
+
+    let dated = r#"<DT><H3 ADD_DATE="1600910474" LAST_MODIFIED="1600910622">dated</H3>
+<DL><p>
+</DL><p>"#;
+    let undated = r#"<DT><H3>undated</H3>
+<DL><p>
+</DL><p>"#;
+
+    for item in &[dated, undated] {
+        let document = parse_html().one(*item);
+        let h3 = document.select_first("H3").unwrap();
+
+        assert_eq!(
+            Folder::from_node(h3.as_node()).unwrap().render().unwrap(),
+            *item
+        );
+    }
+}
+
+#[test]
+fn should_partition_folder_children() {
+    let folder = FolderBuilder::default()
+        .title("root")
+        .children(vec![
+            Item::Shortcut(Bookmark::from(("first", "url1"))),
+            Item::Subfolder(
+                FolderBuilder::default()
+                    .title("nested")
+                    .children(vec![Item::Shortcut(Bookmark::from(("inner", "url2")))])
+                    .build()
+                    .unwrap(),
+            ),
+            Item::Shortcut(Bookmark::from(("second", "url3"))),
+        ])
+        .build()
+        .unwrap();
+
+    let (folders, bookmarks) = folder.partition();
+
+    assert_eq!(folders.len(), 1);
+    assert_eq!(folders[0].title, "nested");
+    assert_eq!(
+        bookmarks
+            .iter()
+            .map(|b| b.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["first", "second"]
+    );
+}
+
+#[test]
+fn should_build_folder_from_bookmarks_and_subfolders() {
+    let nested = FolderBuilder::default()
+        .title("nested")
+        .bookmarks(vec![Bookmark::from(("inner", "url2"))])
+        .build()
+        .unwrap();
+
+    let folder = FolderBuilder::default()
+        .title("root")
+        .bookmarks(vec![Bookmark::from(("first", "url1"))])
+        .subfolders(vec![nested.clone()])
+        .bookmarks(vec![Bookmark::from(("second", "url3"))])
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        folder.children,
+        vec![
+            Item::Shortcut(Bookmark::from(("first", "url1"))),
+            Item::Subfolder(nested),
+            Item::Shortcut(Bookmark::from(("second", "url3"))),
+        ]
+    );
+}
@@ -0,0 +1,84 @@
+//! Contains the stable hash of the structure of the items.
+use crate::item::Item;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Implements the 64 bits FNV-1a hash, its value doesn't depend on the platform or the Rust version.
+pub(crate) struct ContentHasher(u64);
+
+impl ContentHasher {
+    pub(crate) fn new() -> Self {
+        ContentHasher(FNV_OFFSET)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// Writes a length-prefixed string, so that consecutive strings can't be confused.
+    pub(crate) fn write_str(&mut self, value: &str) {
+        self.write(&(value.len() as u64).to_le_bytes());
+        self.write(value.as_bytes());
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Hashes the folder titles, the bookmark `href`s and `title`s and their nesting, the dates are ignored.
+pub(crate) fn hash_children(children: &[Item], hasher: &mut ContentHasher) {
+    hasher.write(&(children.len() as u64).to_le_bytes());
+
+    for item in children {
+        match item {
+            Item::Subfolder(f) => {
+                hasher.write(b"F");
+                hasher.write_str(&f.title);
+                hash_children(&f.children, hasher);
+            }
+            Item::Shortcut(b) => {
+                hasher.write(b"B");
+                hasher.write_str(&b.href);
+                hasher.write_str(&b.title);
+            }
+        }
+    }
+}
+
+#[test]
+fn should_hash_structure_only() {
+    use crate::bookmark::BookmarkBuilder;
+    use crate::folder::FolderBuilder;
+
+    let hash = |children: &[Item]| {
+        let mut hasher = ContentHasher::new();
+        hash_children(children, &mut hasher);
+        hasher.finish()
+    };
+
+    let bookmark = BookmarkBuilder::default()
+        .href("url")
+        .title("name")
+        .build()
+        .unwrap();
+    let mut dated = bookmark.clone();
+    dated.add_date = String::from("1466009059");
+
+    let folder = FolderBuilder::default()
+        .title("name")
+        .children(vec![Item::Shortcut(bookmark.clone())])
+        .build()
+        .unwrap();
+
+    let flat = vec![Item::Shortcut(bookmark.clone())];
+    let nested = vec![Item::Subfolder(folder)];
+
+    assert_eq!(hash(&flat), hash(&[Item::Shortcut(dated)]));
+    assert_ne!(hash(&flat), hash(&nested));
+    assert_ne!(hash(&flat), hash(&[]));
+}
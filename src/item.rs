@@ -1,7 +1,12 @@
 //! Contains the [Item] enum that dispatches all the implementations of the items.
+use chrono::{DateTime, Utc};
 use kuchiki::NodeRef;
 use serde::Serialize;
 
+use crate::date::parse_date;
+use crate::lenient::parse_document;
+use crate::node_ref_ext::*;
+use crate::parse::ParseOptions;
 use crate::Bookmark;
 use crate::Folder;
 
@@ -23,16 +28,51 @@ pub enum Item {
 
 impl Item {
     /// Creates a [Folder] or a [Bookmark] models from any given item.
+    ///
+    /// The `node` is expected to be a `<DT/>` element, or directly its `<A/>` or `<H3/>` child.
+    /// The node of a folder should stay attached to its document, its children are searched among its siblings.
     pub fn from_node(node: &NodeRef) -> Option<Self> {
-        if let Some(bookmark) = Bookmark::from_node(node) {
+        Item::from_node_with(node, &ParseOptions::default())
+    }
+
+    /// Creates a [Folder] or a [Bookmark] models from any given item, customized by the [ParseOptions].
+    pub fn from_node_with(node: &NodeRef, options: &ParseOptions) -> Option<Self> {
+        Item::from_node_observed(node, options, &mut || {})
+    }
+
+    /// Creates a [Folder] or a [Bookmark] models from any given item, `on_item` is called after every parsed nested item.
+    pub(crate) fn from_node_observed(
+        node: &NodeRef,
+        options: &ParseOptions,
+        on_item: &mut dyn FnMut(),
+    ) -> Option<Self> {
+        if let Some(bookmark) = Bookmark::from_node_with(node, options) {
             Some(Item::Shortcut(bookmark))
-        } else if let Some(folder) = Folder::from_node(node) {
-            Some(Item::Subfolder(folder))
         } else {
-            None
+            Folder::from_node_observed(node, options, on_item).map(Item::Subfolder)
         }
     }
 
+    /// Creates a [Folder] or a [Bookmark] models from a raw HTML fragment that contains a single item.
+    ///
+    /// ```rust
+    /// use bookmarkt::Item;
+    ///
+    /// let item = Item::from_html(r#"<DT><A HREF="https://www.kernel.org/">Kernel</A>"#).unwrap();
+    ///
+    /// assert_eq!(item.take_shortcut().unwrap().title, "Kernel");
+    /// ```
+    pub fn from_html(fragment: &str) -> Option<Self> {
+        let document = parse_document(fragment);
+
+        let node = match document.select_elements("DT").into_iter().next() {
+            Some(dt) => Some(dt),
+            None => document.select_elements("A, H3").into_iter().next(),
+        };
+
+        node.and_then(|node| Item::from_node(&node))
+    }
+
     /// Checks if the item is a shortcut
     pub fn is_shortcut(&self) -> bool {
         match self {
@@ -64,6 +104,39 @@ impl Item {
             Item::Shortcut(_) => None,
         }
     }
+
+    /// Gets the title of the folder or of the shortcut
+    pub fn title(&self) -> &str {
+        match self {
+            Item::Subfolder(folder) => &folder.title,
+            Item::Shortcut(bookmark) => &bookmark.title,
+        }
+    }
+
+    /// Gets the `href` of the shortcut, it is [None] for a subfolder
+    pub fn href(&self) -> Option<&str> {
+        match self {
+            Item::Subfolder(_) => None,
+            Item::Shortcut(bookmark) => Some(&bookmark.href),
+        }
+    }
+
+    /// Gets the `add_date` attribute of the folder or of the shortcut as a date,
+    /// it is [None] when the attribute is empty or malformed
+    pub fn added(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Item::Subfolder(folder) => parse_date(&folder.add_date),
+            Item::Shortcut(bookmark) => bookmark.added(),
+        }
+    }
+
+    /// Gets the direct children of the subfolder, they are empty for a shortcut
+    pub fn children(&self) -> &[Item] {
+        match self {
+            Item::Subfolder(folder) => &folder.children,
+            Item::Shortcut(_) => &[],
+        }
+    }
 }
 
 impl PartialEq for Item {
@@ -75,3 +148,38 @@ impl PartialEq for Item {
         }
     }
 }
+
+/// Creates a test folder item with the `title` and the `children`.
+#[cfg(test)]
+pub(crate) fn folder_item(title: &str, children: Vec<Item>) -> Item {
+    Item::Subfolder(Folder {
+        title: String::from(title),
+        children,
+        ..Folder::default()
+    })
+}
+
+/// Creates a test shortcut item with the `title` and the `href`.
+#[cfg(test)]
+pub(crate) fn bookmark_item(title: &str, href: &str) -> Item {
+    Item::Shortcut(Bookmark::from((title, href)))
+}
+
+#[test]
+fn should_parse_item_fragments() {
+    let folder = r#"
+    <DT><H3>folder</H3>
+    <DL><p>
+        <DT><A HREF="url">name</A>
+    </DL><p>"#;
+
+    let parsed = Item::from_html(folder).unwrap();
+    let subfolder = parsed.take_subfolder().unwrap();
+
+    assert_eq!(subfolder.title, "folder");
+    assert!(subfolder.children[0].is_shortcut());
+    assert!(Item::from_html(r#"<A HREF="url">name</A>"#)
+        .unwrap()
+        .is_shortcut());
+    assert_eq!(Item::from_html("<p>nothing</p>"), None);
+}
@@ -0,0 +1,125 @@
+//! Contains the repair of the legacy documents, e.g. the Netscape 4 exports, that omit the closing tags of their items.
+use kuchiki::parse_html;
+use kuchiki::traits::TendrilSink;
+use kuchiki::NodeRef;
+use std::borrow::Cow;
+
+/// The tags that start a new entry, they close the `<A/>` or the `<H3/>` left open before them.
+const DELIMITERS: [&str; 7] = ["A", "H3", "DT", "DD", "DL", "P", "HR"];
+
+/// Closes the `<A/>` and `<H3/>` tags left open, right after their text.
+///
+/// An open tag is closed by the next tag that starts an entry, e.g. a `<DT/>` or a `</DL>`, or by the end of the text.
+/// Without the fix, the HTML parser keeps the whitespace in the titles, and an open `<H3/>` swallows the rest of the document.
+/// The documents whose tags are all closed are returned as they are.
+pub(crate) fn close_unclosed_items(raw: &str) -> Cow<'_, str> {
+    let mut insertions: Vec<(usize, &str)> = vec![];
+    let mut open: Option<(&str, usize)> = None;
+    let mut position = 0;
+
+    while let Some(start) = raw[position..].find('<').map(|start| position + start) {
+        if let Some((_, text_end)) = open.as_mut() {
+            let text = &raw[position..start];
+
+            if !text.trim().is_empty() {
+                *text_end = position + text.trim_end().len();
+            }
+        }
+
+        if raw[start..].starts_with("<!--") {
+            position = raw[start..]
+                .find("-->")
+                .map_or(raw.len(), |end| start + end + 3);
+            continue;
+        }
+
+        let tag = &raw[start + 1..];
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_ascii_uppercase();
+
+        if name.is_empty() {
+            position = start + 1;
+            continue;
+        }
+
+        let end = tag.find('>').map_or(raw.len(), |end| start + end + 2);
+
+        match open {
+            Some((open_name, _)) if closing && name == open_name => open = None,
+            Some((open_name, text_end)) if DELIMITERS.contains(&name.as_str()) => {
+                insertions.push((text_end, closing_tag(open_name)));
+                open = None;
+            }
+            Some((_, ref mut text_end)) => *text_end = end,
+            None => {}
+        }
+
+        if !closing && (name == "A" || name == "H3") {
+            open = Some((if name == "A" { "A" } else { "H3" }, end));
+        }
+
+        position = end;
+    }
+
+    if let Some((open_name, mut text_end)) = open {
+        let text = &raw[position.min(raw.len())..];
+
+        if !text.trim().is_empty() {
+            text_end = position + text.trim_end().len();
+        }
+
+        insertions.push((text_end, closing_tag(open_name)));
+    }
+
+    if insertions.is_empty() {
+        return Cow::Borrowed(raw);
+    }
+
+    let mut closed = String::with_capacity(raw.len() + insertions.len() * 5);
+    let mut copied = 0;
+
+    for (index, tag) in insertions {
+        closed.push_str(&raw[copied..index]);
+        closed.push_str(tag);
+        copied = index;
+    }
+
+    closed.push_str(&raw[copied..]);
+    Cow::Owned(closed)
+}
+
+/// Parses a raw document, once its unclosed items are closed.
+/// All the entry points that parse a raw text go through it.
+pub(crate) fn parse_document(raw: &str) -> NodeRef {
+    parse_html().one(close_unclosed_items(raw).as_ref())
+}
+
+fn closing_tag(name: &str) -> &'static str {
+    if name == "A" {
+        "</A>"
+    } else {
+        "</H3>"
+    }
+}
+
+#[test]
+fn should_close_unclosed_items() {
+    let raw = "<DT><A HREF=\"a\">a\n<DT><H3>f\n<DL><p>\n<DT><A HREF=\"b\"><B>b</B> c\n</DL><p>\n<DT><A HREF=\"d\">d";
+
+    assert_eq!(
+        close_unclosed_items(raw),
+        "<DT><A HREF=\"a\">a</A>\n<DT><H3>f</H3>\n<DL><p>\n<DT><A HREF=\"b\"><B>b</B> c</A>\n</DL><p>\n<DT><A HREF=\"d\">d</A>"
+    );
+}
+
+#[test]
+fn should_keep_closed_items() {
+    let raw = "<DT><A HREF=\"a\">a < b</A>\n<!-- <DT><A> -->\n<DT><H3>f</H3>\n";
+
+    assert!(matches!(close_unclosed_items(raw), Cow::Borrowed(_)));
+}
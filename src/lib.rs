@@ -20,9 +20,13 @@
 //! assert_eq!(parsed.children.len(), 2);
 //! ```
 //!
+//! All the models and their builders can be imported at once with the [prelude].
+//!
 //! The bookmarkt structures also support the *import* and *export* features.
 //!
 //! ```rust
+//! # #[cfg(feature = "render")]
+//! # {
 //! use bookmarkt::Netscape;
 //! use serde_json;
 //! use std::path::Path;
@@ -33,8 +37,17 @@
 //! let reimported = Netscape::from_html(&exported).unwrap();
 //!
 //! assert_eq!(imported, reimported);
+//! # }
 //! ```
 //!
+//! # Features
+//!
+//! * `render` (default) renders the models as Netscape documents with the [askama](https://github.com/djc/askama) templates.
+//!   Without it, e.g. with `default-features = false`, the crate only parses and doesn't depend on askama.
+//! * `online` fetches the favicons of the bookmarks over the network.
+//! * `sqlite` imports the Firefox profiles from their `places.sqlite` database.
+//! * `testing` exposes the `testing` helpers that check the round-trip of real documents.
+//!
 //! # Acknowledgment
 //!
 //! `bookmarkt` uses the following dependencies :
@@ -55,15 +68,59 @@
 #[macro_use]
 extern crate derive_builder;
 
+#[cfg(feature = "render")]
+mod append;
+mod chrome;
+mod chunk;
 mod collection;
+mod date;
+#[cfg(feature = "render")]
+mod diff;
+mod directory;
+mod dot;
+mod escape;
+mod favicon;
+mod hash;
+mod lenient;
 mod node_ref_ext;
+mod normalize;
 
 mod bookmark;
 mod folder;
 mod item;
+mod lint;
+mod merge;
+mod mojibake;
 mod netscape;
+mod parse;
+#[cfg(feature = "sqlite")]
+mod places;
+mod pocket;
+mod preserve;
+mod preview;
+mod render;
+mod source;
+mod stats;
 
-pub use bookmark::Bookmark;
-pub use folder::Folder;
+pub mod prelude;
+#[cfg(all(feature = "render", any(test, feature = "testing")))]
+pub mod testing;
+
+pub use bookmark::{Bookmark, BookmarkBuilder, BookmarkKey};
+pub use folder::{Folder, FolderBuilder};
 pub use item::Item;
-pub use netscape::Netscape;
+pub use lint::LintIssue;
+pub use merge::MergeStrategy;
+pub use netscape::{Netscape, NetscapeBuilder};
+pub use node_ref_ext::NodeRefExt;
+pub use parse::ParseOptions;
+pub use preserve::PreservedSource;
+pub use preview::ChangePreview;
+pub use render::Renderer;
+#[cfg(feature = "render")]
+pub use render::{EscapePolicy, LineEnding, RenderOptions};
+pub use source::BrowserHint;
+pub use stats::Stats;
+
+#[cfg(feature = "online")]
+pub use favicon::FaviconOptions;
@@ -0,0 +1,206 @@
+//! Contains the [LintIssue] enum reported by the health check of a [Netscape] document.
+//!
+//! [Netscape]: crate::Netscape
+use serde::Serialize;
+use std::collections::HashSet;
+use url::Url;
+
+use crate::date::parse_date;
+use crate::item::Item;
+
+/// Represents the issues found by the health check of a document.
+///
+/// The `path` of an issue lists the folder titles from the root of the document to the offending item.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "issue", rename_all = "snake_case")]
+pub enum LintIssue {
+    /// The `DuplicateHref` issue is reported for every [Bookmark] whose `href` was already seen.
+    ///
+    /// [Bookmark]: crate::Bookmark
+    DuplicateHref {
+        /// The path of the folder that contains the duplicate.
+        path: Vec<String>,
+        /// The duplicated url.
+        href: String,
+    },
+
+    /// The `EmptyTitle` issue is reported for a [Bookmark] without title.
+    ///
+    /// [Bookmark]: crate::Bookmark
+    EmptyTitle {
+        /// The path of the folder that contains the bookmark.
+        path: Vec<String>,
+        /// The url of the bookmark.
+        href: String,
+    },
+
+    /// The `InvalidUrl` issue is reported for a [Bookmark] whose `href` can't be parsed.
+    ///
+    /// [Bookmark]: crate::Bookmark
+    InvalidUrl {
+        /// The path of the folder that contains the bookmark.
+        path: Vec<String>,
+        /// The malformed url.
+        href: String,
+    },
+
+    /// The `EmptyFolder` issue is reported for a [Folder] without children.
+    ///
+    /// [Folder]: crate::Folder
+    EmptyFolder {
+        /// The path of the empty folder, its own title included.
+        path: Vec<String>,
+    },
+
+    /// The `MalformedDate` issue is reported for a non-empty date attribute that isn't a UNIX time.
+    MalformedDate {
+        /// The path of the item, its own title included.
+        path: Vec<String>,
+        /// The name of the date attribute.
+        attribute: String,
+        /// The malformed value.
+        value: String,
+    },
+}
+
+/// Checks all the nested items, the issues are reported in the document order.
+pub(crate) fn lint(children: &[Item]) -> Vec<LintIssue> {
+    let mut issues = vec![];
+    lint_children(children, &mut vec![], &mut HashSet::new(), &mut issues);
+    issues
+}
+
+fn lint_children(
+    children: &[Item],
+    path: &mut Vec<String>,
+    hrefs: &mut HashSet<String>,
+    issues: &mut Vec<LintIssue>,
+) {
+    for item in children {
+        match item {
+            Item::Subfolder(f) => {
+                path.push(f.title.clone());
+
+                lint_date(path, "add_date", &f.add_date, issues);
+                lint_date(path, "last_modified", &f.last_modified, issues);
+
+                if f.children.is_empty() {
+                    issues.push(LintIssue::EmptyFolder { path: path.clone() });
+                }
+
+                lint_children(&f.children, path, hrefs, issues);
+                path.pop();
+            }
+            Item::Shortcut(b) => {
+                if !hrefs.insert(b.href.clone()) {
+                    issues.push(LintIssue::DuplicateHref {
+                        path: path.clone(),
+                        href: b.href.clone(),
+                    });
+                }
+
+                if b.title.trim().is_empty() {
+                    issues.push(LintIssue::EmptyTitle {
+                        path: path.clone(),
+                        href: b.href.clone(),
+                    });
+                }
+
+                if Url::parse(&b.href).is_err() {
+                    issues.push(LintIssue::InvalidUrl {
+                        path: path.clone(),
+                        href: b.href.clone(),
+                    });
+                }
+
+                path.push(b.title.clone());
+                lint_date(path, "add_date", &b.add_date, issues);
+                lint_date(path, "last_visit", &b.last_visit, issues);
+                lint_date(path, "last_modified", &b.last_modified, issues);
+                path.pop();
+            }
+        }
+    }
+}
+
+fn lint_date(path: &[String], attribute: &str, value: &str, issues: &mut Vec<LintIssue>) {
+    if !value.is_empty() && parse_date(value).is_none() {
+        issues.push(LintIssue::MalformedDate {
+            path: path.to_vec(),
+            attribute: String::from(attribute),
+            value: String::from(value),
+        });
+    }
+}
+
+#[test]
+fn should_lint_nested_items() {
+    use crate::bookmark::BookmarkBuilder;
+    use crate::folder::FolderBuilder;
+
+    let valid = BookmarkBuilder::default()
+        .href("https://www.kernel.org/")
+        .title("kernel")
+        .add_date("1466009167")
+        .build()
+        .unwrap();
+
+    let invalid = BookmarkBuilder::default()
+        .href("kernel")
+        .title("")
+        .last_visit("yesterday")
+        .build()
+        .unwrap();
+
+    let empty = FolderBuilder::default().title("empty").build().unwrap();
+    let folder = FolderBuilder::default()
+        .title("folder")
+        .children(vec![Item::Shortcut(valid.clone()), Item::Subfolder(empty)])
+        .build()
+        .unwrap();
+
+    let children = vec![
+        Item::Shortcut(valid),
+        Item::Subfolder(folder),
+        Item::Shortcut(invalid),
+    ];
+
+    let folder = vec![String::from("folder")];
+    let empty = vec![String::from("folder"), String::from("empty")];
+
+    assert_eq!(
+        lint(&children),
+        vec![
+            LintIssue::DuplicateHref {
+                path: folder,
+                href: String::from("https://www.kernel.org/"),
+            },
+            LintIssue::EmptyFolder { path: empty },
+            LintIssue::EmptyTitle {
+                path: vec![],
+                href: String::from("kernel"),
+            },
+            LintIssue::InvalidUrl {
+                path: vec![],
+                href: String::from("kernel"),
+            },
+            LintIssue::MalformedDate {
+                path: vec![String::new()],
+                attribute: String::from("last_visit"),
+                value: String::from("yesterday"),
+            },
+        ]
+    );
+}
+
+#[test]
+fn should_serialize_json_lint_issue() {
+    let issue = LintIssue::EmptyFolder {
+        path: vec![String::from("folder")],
+    };
+
+    assert_eq!(
+        serde_json::to_string(&issue).unwrap(),
+        r#"{"issue":"empty_folder","path":["folder"]}"#
+    );
+}
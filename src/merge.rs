@@ -0,0 +1,452 @@
+//! Contains the [MergeStrategy] that resolves the conflicts between the bookmarks of two merged documents,
+//! and the three-way merge of the documents.
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+use crate::collection::NestedCollection;
+use crate::date::parse_date;
+use crate::item::Item;
+use crate::{Bookmark, Folder};
+
+/// Implements the resolutions of two [Bookmark]s that share the same `href` in the same merged folder.
+#[derive(Default)]
+pub enum MergeStrategy {
+    /// The `KeepSelf` strategy keeps the bookmark of the document being merged into.
+    #[default]
+    KeepSelf,
+
+    /// The `KeepOther` strategy keeps the bookmark of the merged document.
+    KeepOther,
+
+    /// The `KeepNewer` strategy keeps the bookmark with the latest `last_modified` date,
+    /// the bookmark of the document being merged into is kept on equal or missing dates.
+    KeepNewer,
+
+    /// The `Custom` strategy builds the kept bookmark from the bookmark of the document being merged into
+    /// and the bookmark of the merged document, in this order.
+    #[allow(clippy::type_complexity)]
+    Custom(Box<dyn Fn(&Bookmark, &Bookmark) -> Bookmark>),
+}
+
+impl MergeStrategy {
+    /// Resolves the conflict between the bookmark of `self` and the one of the `other` document.
+    fn resolve(&self, mine: &Bookmark, theirs: Bookmark) -> Bookmark {
+        match self {
+            MergeStrategy::KeepSelf => mine.clone(),
+            MergeStrategy::KeepOther => theirs,
+            MergeStrategy::KeepNewer => {
+                let newer = match (
+                    parse_date(&mine.last_modified),
+                    parse_date(&theirs.last_modified),
+                ) {
+                    (Some(mine), Some(theirs)) => theirs > mine,
+                    (None, Some(_)) => true,
+                    _ => false,
+                };
+
+                if newer {
+                    theirs
+                } else {
+                    mine.clone()
+                }
+            }
+            MergeStrategy::Custom(resolve) => resolve(mine, &theirs),
+        }
+    }
+}
+
+impl fmt::Debug for MergeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MergeStrategy::KeepSelf => f.write_str("KeepSelf"),
+            MergeStrategy::KeepOther => f.write_str("KeepOther"),
+            MergeStrategy::KeepNewer => f.write_str("KeepNewer"),
+            MergeStrategy::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Merges the `other` items into the `children`, the folders are matched by their titles at every level
+/// and the bookmarks by their `href` within a matched level. The unmatched items are appended in order.
+pub(crate) fn merge_children(children: &mut Vec<Item>, other: Vec<Item>, strategy: &MergeStrategy) {
+    for item in other {
+        match item {
+            Item::Subfolder(f) => {
+                let matching = children.iter_mut().find_map(|item| match item {
+                    Item::Subfolder(existing) if existing.title == f.title => Some(existing),
+                    _ => None,
+                });
+
+                match matching {
+                    Some(existing) => merge_children(&mut existing.children, f.children, strategy),
+                    None => children.push(Item::Subfolder(f)),
+                }
+            }
+            Item::Shortcut(b) => {
+                let matching = children.iter_mut().find_map(|item| match item {
+                    Item::Shortcut(existing) if existing.href == b.href => Some(existing),
+                    _ => None,
+                });
+
+                match matching {
+                    Some(existing) => *existing = strategy.resolve(existing, b),
+                    None => children.push(Item::Shortcut(b)),
+                }
+            }
+        }
+    }
+}
+
+/// Applies the changes made in `theirs` since the `base` onto a copy of `ours`, the bookmarks are matched by `href`.
+/// The conflicting changes are resolved in favor of `ours`, see [Netscape::patch_from].
+///
+/// [Netscape::patch_from]: crate::Netscape::patch_from
+pub(crate) fn patch_children(ours: &Vec<Item>, base: &Vec<Item>, theirs: &Vec<Item>) -> Vec<Item> {
+    let our_index = index_by_href(ours);
+    let base_index = index_by_href(base);
+    let their_index = index_by_href(theirs);
+
+    let base_folders = folder_contents(base, &mut vec![]);
+    let their_folders = folder_contents(theirs, &mut vec![]);
+    let renames = folder_renames(&base_folders, &their_folders);
+
+    let mut patched = ours.clone();
+
+    for (old, new) in renames.iter() {
+        let current: Vec<String> = new[..new.len() - 1]
+            .iter()
+            .chain(old.last())
+            .cloned()
+            .collect();
+
+        if find_folder(&mut patched, new).is_none() {
+            if let Some(folder) = find_folder(&mut patched, &current) {
+                folder.title = new[new.len() - 1].clone();
+            }
+        }
+    }
+
+    let removed: HashSet<&str> = base_index
+        .iter()
+        .filter(|(href, (_, b))| {
+            !their_index.contains_key(*href)
+                && our_index
+                    .get(*href)
+                    .is_some_and(|(_, o)| same_attributes(o, b))
+        })
+        .map(|(href, _)| *href)
+        .collect();
+
+    patched.drain_shortcuts(&|b| removed.contains(b.href.as_str()));
+
+    let mut seen = HashSet::new();
+
+    for (their_path, their) in theirs.shortcuts_with_path() {
+        if !seen.insert(their.href.as_str()) {
+            continue;
+        }
+
+        match (
+            base_index.get(their.href.as_str()),
+            our_index.get(their.href.as_str()),
+        ) {
+            (None, None) => patched
+                .children_at_path(&their_path)
+                .push(Item::Shortcut(their.clone())),
+            (Some((_, base)), None) if !same_attributes(base, their) => patched
+                .children_at_path(&their_path)
+                .push(Item::Shortcut(their.clone())),
+            (Some((base_path, base)), Some((our_path, _))) => {
+                if let Some(our) = patched
+                    .shortcuts_mut()
+                    .into_iter()
+                    .find(|b| b.href == their.href)
+                {
+                    for ((mine, original), changed) in IntoIterator::into_iter(attributes_mut(our))
+                        .zip(attributes(base))
+                        .zip(attributes(their))
+                    {
+                        if changed != original && *mine == *original {
+                            *mine = changed.clone();
+                        }
+                    }
+                }
+
+                let base_path = translate(&renames, base_path);
+
+                if their_path != base_path && translate(&renames, our_path) == base_path {
+                    let moved = patched.drain_shortcuts(&|b| b.href == their.href);
+                    let destination = patched.children_at_path(&their_path);
+
+                    destination.extend(moved.into_iter().map(Item::Shortcut));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let their_paths: HashSet<&Vec<String>> = their_folders.iter().map(|(path, _)| path).collect();
+
+    for (path, _) in base_folders.iter().rev() {
+        if their_paths.contains(path) || renames.iter().any(|(old, _)| old == path) {
+            continue;
+        }
+
+        remove_empty_folder(&mut patched, &translate(&renames, path));
+    }
+
+    patched
+}
+
+/// Lists the nested folders with their path, and the `href` of their own bookmarks.
+fn folder_contents(
+    children: &[Item],
+    path: &mut Vec<String>,
+) -> Vec<(Vec<String>, BTreeSet<String>)> {
+    let mut folders = vec![];
+
+    for item in children {
+        if let Item::Subfolder(f) = item {
+            path.push(f.title.clone());
+
+            let hrefs = f
+                .children
+                .iter()
+                .filter_map(|child| match child {
+                    Item::Shortcut(b) => Some(b.href.clone()),
+                    Item::Subfolder(_) => None,
+                })
+                .collect();
+
+            folders.push((path.clone(), hrefs));
+            folders.append(&mut folder_contents(&f.children, path));
+            path.pop();
+        }
+    }
+
+    folders
+}
+
+/// Finds the `(old path, new path)` of the folders renamed in `theirs`, in the document order.
+/// A base folder is renamed when its path is gone from `theirs`, and a new folder of the same parent
+/// holds the same bookmarks. The empty folders are never recognized as renamed.
+fn folder_renames(
+    base: &[(Vec<String>, BTreeSet<String>)],
+    theirs: &[(Vec<String>, BTreeSet<String>)],
+) -> Vec<(Vec<String>, Vec<String>)> {
+    let base_paths: HashSet<&Vec<String>> = base.iter().map(|(path, _)| path).collect();
+    let their_paths: HashSet<&Vec<String>> = theirs.iter().map(|(path, _)| path).collect();
+    let mut renames: Vec<(Vec<String>, Vec<String>)> = vec![];
+
+    for (old, hrefs) in base {
+        if hrefs.is_empty() || their_paths.contains(old) {
+            continue;
+        }
+
+        let parent = translate(&renames, &old[..old.len() - 1]);
+        let renamed = theirs.iter().find(|(new, their_hrefs)| {
+            their_hrefs == hrefs
+                && new[..new.len() - 1] == parent[..]
+                && !base_paths.contains(new)
+                && !renames.iter().any(|(_, renamed)| renamed == new)
+        });
+
+        if let Some((new, _)) = renamed {
+            renames.push((old.clone(), new.clone()));
+        }
+    }
+
+    renames
+}
+
+/// Translates a base `path` through the longest renamed folder that contains it.
+fn translate<S: AsRef<str>>(renames: &[(Vec<String>, Vec<String>)], path: &[S]) -> Vec<String> {
+    let path: Vec<String> = path
+        .iter()
+        .map(|title| title.as_ref().to_string())
+        .collect();
+
+    renames
+        .iter()
+        .filter(|(old, _)| path.starts_with(old))
+        .max_by_key(|(old, _)| old.len())
+        .map_or_else(
+            || path.clone(),
+            |(old, new)| new.iter().chain(&path[old.len()..]).cloned().collect(),
+        )
+}
+
+fn find_folder<'a>(children: &'a mut [Item], path: &[String]) -> Option<&'a mut Folder> {
+    let (title, rest) = path.split_first()?;
+
+    children
+        .iter_mut()
+        .find_map(|item| match item {
+            Item::Subfolder(f) if f.title == *title => Some(f),
+            _ => None,
+        })
+        .and_then(|folder| {
+            if rest.is_empty() {
+                Some(folder)
+            } else {
+                find_folder(&mut folder.children, rest)
+            }
+        })
+}
+
+/// Removes the folder at the `path` when it holds no bookmark, e.g. once the bookmarks removed in `theirs` are gone.
+fn remove_empty_folder(children: &mut Vec<Item>, path: &[String]) {
+    let (parent, title) = match path.split_last() {
+        Some((title, parent)) => (parent, title),
+        None => return,
+    };
+
+    let siblings = match parent {
+        [] => children,
+        parent => match find_folder(children, parent) {
+            Some(folder) => &mut folder.children,
+            None => return,
+        },
+    };
+
+    if let Some(position) = siblings.iter().position(|item| {
+        matches!(item, Item::Subfolder(f) if f.title == *title && f.children.shortcuts().is_empty())
+    }) {
+        siblings.remove(position);
+    }
+}
+
+/// Indexes the first nested [Bookmark] of every `href`, with the titles path of its folder.
+fn index_by_href(children: &Vec<Item>) -> HashMap<&str, (Vec<&str>, &Bookmark)> {
+    let mut index = HashMap::new();
+
+    for (path, bookmark) in children.shortcuts_with_path() {
+        index
+            .entry(bookmark.href.as_str())
+            .or_insert((path, bookmark));
+    }
+
+    index
+}
+
+pub(crate) fn attributes(b: &Bookmark) -> [&String; 10] {
+    [
+        &b.title,
+        &b.add_date,
+        &b.last_visit,
+        &b.last_modified,
+        &b.icon_uri,
+        &b.icon,
+        &b.target,
+        &b.rel,
+        &b.shortcut_url,
+        &b.description,
+    ]
+}
+
+fn attributes_mut(b: &mut Bookmark) -> [&mut String; 10] {
+    [
+        &mut b.title,
+        &mut b.add_date,
+        &mut b.last_visit,
+        &mut b.last_modified,
+        &mut b.icon_uri,
+        &mut b.icon,
+        &mut b.target,
+        &mut b.rel,
+        &mut b.shortcut_url,
+        &mut b.description,
+    ]
+}
+
+pub(crate) fn same_attributes(a: &Bookmark, b: &Bookmark) -> bool {
+    attributes(a) == attributes(b)
+}
+
+#[test]
+fn should_resolve_merge_conflicts() {
+    let mine = Bookmark {
+        title: String::from("mine"),
+        last_modified: String::from("2"),
+        ..Bookmark::default()
+    };
+    let theirs = |last_modified: &str| Bookmark {
+        title: String::from("theirs"),
+        last_modified: String::from(last_modified),
+        ..Bookmark::default()
+    };
+    let title = |strategy: MergeStrategy, last_modified: &str| {
+        strategy.resolve(&mine, theirs(last_modified)).title
+    };
+
+    assert_eq!(title(MergeStrategy::KeepSelf, "3"), "mine");
+    assert_eq!(title(MergeStrategy::KeepOther, "1"), "theirs");
+    assert_eq!(title(MergeStrategy::KeepNewer, "3"), "theirs");
+    assert_eq!(title(MergeStrategy::KeepNewer, "2"), "mine");
+    assert_eq!(title(MergeStrategy::KeepNewer, ""), "mine");
+
+    let custom = MergeStrategy::Custom(Box::new(|mine, theirs| {
+        mine.clone()
+            .with_title(format!("{} / {}", mine.title, theirs.title))
+    }));
+    assert_eq!(title(custom, ""), "mine / theirs");
+}
+
+#[test]
+fn should_patch_changes_of_theirs() {
+    use crate::item::{bookmark_item as bookmark, folder_item as folder};
+
+    let base = vec![
+        bookmark("a", "url-a"),
+        bookmark("b", "url-b"),
+        folder("f", vec![bookmark("c", "url-c")]),
+    ];
+    let ours = vec![
+        bookmark("a (ours)", "url-a"),
+        bookmark("b", "url-b"),
+        folder("f", vec![bookmark("c", "url-c")]),
+    ];
+    let theirs = vec![
+        bookmark("a (theirs)", "url-a"),
+        bookmark("b (theirs)", "url-b"),
+        folder("g", vec![bookmark("c", "url-c")]),
+    ];
+
+    let patched = patch_children(&ours, &base, &theirs);
+
+    assert_eq!(
+        patched,
+        vec![
+            bookmark("a (ours)", "url-a"),
+            bookmark("b (theirs)", "url-b"),
+            folder("g", vec![bookmark("c", "url-c")]),
+        ]
+    );
+}
+
+#[test]
+fn should_patch_folders_of_theirs() {
+    use crate::item::{bookmark_item as bookmark, folder_item as folder};
+
+    let base = vec![
+        folder("f", vec![bookmark("a", "url-a")]),
+        folder("g", vec![bookmark("b", "url-b")]),
+        folder("h", vec![folder("i", vec![bookmark("c", "url-c")])]),
+    ];
+    let ours = vec![
+        folder("f", vec![bookmark("a", "url-a")]),
+        folder("g", vec![bookmark("b", "url-b"), bookmark("d", "url-d")]),
+        folder("h", vec![folder("i", vec![bookmark("c", "url-c")])]),
+    ];
+    let theirs = vec![folder("j", vec![folder("i", vec![bookmark("c", "url-c")])])];
+
+    let patched = patch_children(&ours, &base, &theirs);
+
+    assert_eq!(
+        patched,
+        vec![
+            folder("g", vec![bookmark("d", "url-d")]),
+            folder("j", vec![folder("i", vec![bookmark("c", "url-c")])]),
+        ]
+    );
+}
@@ -0,0 +1,115 @@
+//! Contains the repair of the titles that were decoded twice, e.g. a UTF-8 `é` read as the Windows-1252 `Ã©`.
+use crate::item::Item;
+
+/// The characters of the `0x80..=0x9F` range of Windows-1252, they differ from the Latin-1 control characters.
+const WINDOWS_1252: [(char, u8); 27] = [
+    ('€', 0x80),
+    ('‚', 0x82),
+    ('ƒ', 0x83),
+    ('„', 0x84),
+    ('…', 0x85),
+    ('†', 0x86),
+    ('‡', 0x87),
+    ('ˆ', 0x88),
+    ('‰', 0x89),
+    ('Š', 0x8A),
+    ('‹', 0x8B),
+    ('Œ', 0x8C),
+    ('Ž', 0x8E),
+    ('‘', 0x91),
+    ('’', 0x92),
+    ('“', 0x93),
+    ('”', 0x94),
+    ('•', 0x95),
+    ('–', 0x96),
+    ('—', 0x97),
+    ('˜', 0x98),
+    ('™', 0x99),
+    ('š', 0x9A),
+    ('›', 0x9B),
+    ('œ', 0x9C),
+    ('ž', 0x9E),
+    ('Ÿ', 0x9F),
+];
+
+/// Encodes a character back to its Windows-1252 byte, the undefined bytes are kept as their Latin-1 characters.
+fn windows_1252_byte(c: char) -> Option<u8> {
+    match c as u32 {
+        0..=0x7F | 0xA0..=0xFF => Some(c as u8),
+        0x81 | 0x8D | 0x8F | 0x90 | 0x9D => Some(c as u8),
+        _ => WINDOWS_1252
+            .iter()
+            .find(|(special, _)| *special == c)
+            .map(|(_, byte)| *byte),
+    }
+}
+
+/// Repairs a text decoded as Windows-1252 while it was UTF-8, it is [None] when the text doesn't look double-encoded.
+///
+/// The bytes of the text must be a valid UTF-8 sequence that is shorter than the text itself,
+/// i.e. at least one multibyte character is restored. A text encoded several times is repaired repeatedly.
+pub(crate) fn repair_mojibake(raw: &str) -> Option<String> {
+    let mut repaired: Option<String> = None;
+
+    loop {
+        let current = repaired.as_deref().unwrap_or(raw);
+
+        if current.is_ascii() {
+            return repaired;
+        }
+
+        let bytes: Option<Vec<u8>> = current.chars().map(windows_1252_byte).collect();
+
+        match bytes.and_then(|bytes| String::from_utf8(bytes).ok()) {
+            Some(decoded) if decoded.chars().count() < current.chars().count() => {
+                repaired = Some(decoded)
+            }
+            _ => return repaired,
+        }
+    }
+}
+
+/// Repairs the titles of the nested [Folder]s and [Bookmark]s, it returns the number of repaired titles.
+///
+/// [Folder]: crate::Folder
+/// [Bookmark]: crate::Bookmark
+pub(crate) fn fix_mojibake(children: &mut [Item]) -> usize {
+    let mut count = 0;
+
+    for child in children {
+        let title = match child {
+            Item::Subfolder(f) => {
+                count += fix_mojibake(&mut f.children);
+                &mut f.title
+            }
+            Item::Shortcut(b) => &mut b.title,
+        };
+
+        if let Some(repaired) = repair_mojibake(title) {
+            *title = repaired;
+            count += 1;
+        }
+    }
+
+    count
+}
+
+#[test]
+fn should_repair_double_encoded_titles() {
+    assert_eq!(repair_mojibake("CafÃ©").as_deref(), Some("Café"));
+    assert_eq!(repair_mojibake("Itâ€™s").as_deref(), Some("It’s"));
+    assert_eq!(repair_mojibake("æœ½æœ¨").as_deref(), Some("朽木"));
+    assert_eq!(
+        repair_mojibake("Ã¼ber â€” GrÃ¶ÃŸe").as_deref(),
+        Some("über — Größe")
+    );
+    assert_eq!(repair_mojibake("CafÃƒÂ©").as_deref(), Some("Café"));
+}
+
+#[test]
+fn should_keep_plausible_titles() {
+    assert_eq!(repair_mojibake("Rust"), None);
+    assert_eq!(repair_mojibake("Café"), None);
+    assert_eq!(repair_mojibake("朽木"), None);
+    assert_eq!(repair_mojibake("Größe — über"), None);
+}
@@ -1,15 +1,48 @@
 //! Contains the [Netscape] model and its associated tests.
+#[cfg(feature = "render")]
 use askama::Template;
-use kuchiki::parse_html;
-use kuchiki::traits::TendrilSink;
+use chrono::{DateTime, Datelike, Utc};
 use kuchiki::NodeRef;
 use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::Error;
+#[cfg(feature = "render")]
+use std::io::Write;
 use std::path::Path;
+use url::form_urlencoded::byte_serialize;
+use url::Url;
 
-use crate::collection::NestedCollection;
+#[cfg(feature = "render")]
+use crate::append::append_bookmark_to_file;
+use crate::chrome::from_chrome_json;
+use crate::chunk::chunk_children;
+use crate::collection::{sort_level_by_key, IntoNested, NestedCollection};
+use crate::date::parse_date;
+#[cfg(feature = "render")]
+use crate::diff::{field_difference, first_difference};
+use crate::directory::{export_tree, import_tree};
+use crate::dot::to_dot;
+use crate::escape::escape_html;
+#[cfg(feature = "render")]
+use crate::escape::{escape_document, escape_shell};
+use crate::favicon::favicon_url;
+use crate::hash::{hash_children, ContentHasher};
 use crate::item::Item;
+use crate::lenient::parse_document;
+use crate::lint::{lint, LintIssue};
+use crate::merge::{merge_children, patch_children, MergeStrategy};
+use crate::mojibake::{fix_mojibake, repair_mojibake};
 use crate::node_ref_ext::*;
+use crate::normalize::{normalized_url, strip_query_params, TRACKING_PARAMS};
+use crate::parse::{is_place_query, ParseOptions};
+use crate::pocket::from_pocket;
+use crate::preserve::PreservedSource;
+use crate::preview::ChangePreview;
+use crate::render::{render_children, CompactRenderer, InteractiveRenderer, Renderer};
+#[cfg(feature = "render")]
+use crate::render::{with_attribute_order, EscapePolicy, RenderOptions};
+use crate::source::{detect_source, BrowserHint};
+use crate::stats::Stats;
 
 use crate::Bookmark;
 use crate::Folder;
@@ -27,22 +60,93 @@ use crate::Folder;
 /// This parser isn't strict and will not fail if the specification isn't respected : it implements [Default] trait.
 ///
 /// [Netscape Bookmark File format]: https://docs.microsoft.com/en-us/previous-versions/windows/internet-explorer/ie-developer/platform-apis/aa753582(v=vs.85)?redirectedfrom=MSDN
-#[derive(Serialize, Debug, Template)]
-#[template(path = "netscape.j2", escape = "none")]
+#[derive(Serialize, Builder, Debug, Default)]
+#[cfg_attr(feature = "render", derive(Template))]
+#[cfg_attr(feature = "render", template(path = "netscape.j2", escape = "none"))]
+#[builder(setter(into))]
 pub struct Netscape {
     /// The `title` attribute stores the bookmark document's title, it is the content of the meta tag `<TITLE/>`.
+    #[builder(default)]
     pub title: String,
 
     /// The `h1` attribute stores the root document's title, it is the content of the tag `<H1/>`.
     /// Usually, the `title` and the `h1` attributes have the same content.
+    #[builder(default = "self.default_h1()")]
     pub h1: String,
 
+    /// The `add_date` attribute is the date when the collection was created (in UNIX time), it is read from the `<H1/>` tag.
+    #[builder(default)]
+    pub add_date: String,
+
+    /// The `last_modified` attribute is the date of the last modification of the collection (in UNIX time), it is read from the `<H1/>` tag.
+    #[builder(default)]
+    pub last_modified: String,
+
+    /// The `charset` attribute stores the encoding declared by the `<META/>` tag of the document, as an information.
+    /// The documents are always decoded and rendered as `UTF-8`, so the rendered `<META/>` tag always declares `UTF-8`.
+    #[builder(default)]
+    pub charset: String,
+
+    /// The `metadata` attribute stores the application-specific values of the document, e.g. a sync cursor.
+    /// It is read from and rendered as a `<!-- bookmarkt-meta: {...} -->` comment, the other documents have none.
+    #[builder(default)]
+    pub metadata: HashMap<String, String>,
+
     /// The `children` [Vec] stores all the nested items of the document.
     /// It keeps the **same** order than the initial bookmarks organization.
+    #[builder(default)]
     pub children: Vec<Item>,
+
+    /// The `source` attribute stores the raw text of the parsed document when the whitespace is preserved,
+    /// see [ParseOptions::preserve_whitespace]. It isn't compared nor serialized.
+    #[builder(default)]
+    #[serde(skip)]
+    pub source: Option<PreservedSource>,
 }
 
 impl Netscape {
+    /// The number of parsed items between two calls of the progress callback of [Netscape::from_html_with_progress].
+    pub const PROGRESS_INTERVAL: usize = 1000;
+
+    /// Creates a [NetscapeBuilder] that assembles a [Netscape] model item by item.
+    ///
+    /// ```rust
+    /// use bookmarkt::{Bookmark, Folder, Netscape};
+    ///
+    /// let netscape = Netscape::builder()
+    ///     .title("Bookmarks")
+    ///     .add_folder(Folder::default())
+    ///     .add_bookmark(Bookmark::default())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(netscape.h1, "Bookmarks");
+    /// assert_eq!(netscape.children.len(), 2);
+    /// ```
+    pub fn builder() -> NetscapeBuilder {
+        NetscapeBuilder::default()
+    }
+
+    /// Creates an empty [Netscape] model, its `title` and its `h1` are set independently.
+    /// They are rendered as the distinct `<TITLE/>` and `<H1/>` tags of the document.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    ///
+    /// let netscape = Netscape::new("Bookmarks", "Menu");
+    ///
+    /// assert_eq!(netscape.title, "Bookmarks");
+    /// assert_eq!(netscape.h1, "Menu");
+    /// assert!(netscape.children.is_empty());
+    /// ```
+    pub fn new(title: impl Into<String>, h1: impl Into<String>) -> Self {
+        Netscape {
+            title: title.into(),
+            h1: h1.into(),
+            ..Netscape::default()
+        }
+    }
+
     /// Creates a [Netscape] model from a file path.
     /// It should be priviledged to transform a Netscape File document.
     ///
@@ -55,59 +159,145 @@ impl Netscape {
     ///
     /// println!("{:?}", chromium);
     pub fn from_file(path: &Path) -> Result<Self, Error> {
-        parse_html()
-            .from_utf8()
-            .from_file(path)
-            .and_then(|node| Netscape::from_node(&node))
+        Netscape::from_file_with(path, &ParseOptions::default())
+    }
+
+    /// Creates a [Netscape] model from a file path, customized by the [ParseOptions].
+    /// The bytes are decoded as `UTF-8` whatever the declared `charset`, the invalid sequences are replaced.
+    ///
+    /// ```rust
+    /// use bookmarkt::{Netscape, ParseOptions};
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let options = ParseOptions {
+    ///     allowed_schemes: Some(vec![String::from("https")]),
+    ///     ..ParseOptions::default()
+    /// };
+    /// let chromium = Netscape::from_file_with(path, &options).unwrap();
+    ///
+    /// assert_eq!(chromium.get_bookmarks().len(), 5);
+    /// ```
+    pub fn from_file_with(path: &Path, options: &ParseOptions) -> Result<Self, Error> {
+        let bytes = std::fs::read(path)?;
+
+        Netscape::from_html_with(&String::from_utf8_lossy(&bytes), options)
     }
 
-    /// Creates a [Netscape] model from a parsed a Netscape File DOM
+    /// Creates a [Netscape] model from a parsed Netscape File DOM.
+    ///
+    /// It is the entry point for the documents that the caller already parsed with kuchiki.
+    /// The `node` is either the whole document, its `<HTML/>` element, or any subtree of a larger page:
+    /// a subtree is read from its first `<TITLE/>`, `<META/>`, `<H1/>` and `<DL/>` elements.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use kuchiki::parse_html;
+    /// use kuchiki::traits::TendrilSink;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/netscape.html");
+    /// let document = parse_html().from_utf8().from_file(path).unwrap();
+    /// let netscape = Netscape::from_node(&document).unwrap();
+    ///
+    /// assert_eq!(netscape, Netscape::from_file(path).unwrap());
+    /// ```
     pub fn from_node(node: &NodeRef) -> Result<Self, Error> {
+        Netscape::from_node_with(node, &ParseOptions::default())
+    }
+
+    /// Creates a [Netscape] model from a parsed Netscape File DOM, customized by the [ParseOptions].
+    pub fn from_node_with(node: &NodeRef, options: &ParseOptions) -> Result<Self, Error> {
+        Netscape::from_node_observed(node, options, &mut || {})
+    }
+
+    /// Creates a [Netscape] model from a parsed Netscape File DOM, `on_item` is called after every parsed item.
+    fn from_node_observed(
+        node: &NodeRef,
+        options: &ParseOptions,
+        on_item: &mut dyn FnMut(),
+    ) -> Result<Self, Error> {
         let mut title = String::new();
         let mut h1 = String::new();
+        let mut add_date = String::new();
+        let mut last_modified = String::new();
+        let mut charset = String::new();
         let mut children = vec![];
 
-        let mut head = None;
-        let mut body = None;
+        let mut head = vec![];
+        let mut body = vec![];
 
-        let html = node.children().find(|n| n.is_element("HTML"));
+        let html = if node.is_element("HTML") {
+            Some(node.clone())
+        } else {
+            node.children().find(|n| n.is_element("HTML"))
+        };
 
         if let Some(root) = html {
             for child in root.children() {
                 if child.is_element("HEAD") {
-                    head = Some(child);
+                    head.extend(child.children());
                 } else if child.is_element("BODY") {
-                    body = Some(child);
+                    body.extend(child.children());
                 }
             }
+        } else {
+            // The node is a subtree of a larger document, its first elements of each kind are used.
+            let first = |name: &str| node.inclusive_descendants().find(|n| n.is_element(name));
+
+            head.extend(first("TITLE"));
+            head.extend(first("META"));
+            body.extend(first("H1"));
+            body.extend(first("DL"));
         }
 
-        if let Some(root) = head {
-            for child in root.children() {
-                if child.is_element("TITLE") {
-                    title = child.text_contents();
+        for child in head {
+            if child.is_element("TITLE") {
+                title = child.text_contents();
+            } else if child.is_element("META") {
+                if let Some(declared) = select_charset(&child) {
+                    charset = declared;
                 }
             }
         }
 
-        if let Some(root) = body {
-            for child in root.children() {
-                if child.is_element("H1") {
-                    h1 = child.text_contents();
-                } else if child.is_element("DL") {
-                    for sub in child.children() {
-                        if let Some(item) = Item::from_node(&sub) {
-                            children.push(item);
-                        }
+        for child in body {
+            if child.is_element("H1") {
+                h1 = child.text_contents();
+
+                if let Some(attribute) = child.select_attribute("ADD_DATE") {
+                    add_date = attribute.value;
+                }
+
+                if let Some(attribute) = child.select_attribute("LAST_MODIFIED") {
+                    last_modified = attribute.value;
+                }
+            } else if child.is_element("DL") {
+                for sub in child.children() {
+                    if let Some(item) = Item::from_node_observed(&sub, options, on_item) {
+                        children.push(item);
+                        on_item();
                     }
                 }
             }
         }
 
+        if title.is_empty() && h1.is_empty() {
+            if let Some(default_title) = &options.default_title {
+                title = default_title.clone();
+                h1 = default_title.clone();
+            }
+        }
+
         Ok(Netscape {
-            title: title,
-            h1: h1,
-            children: children,
+            title,
+            h1,
+            add_date,
+            last_modified,
+            charset,
+            metadata: select_metadata(node),
+            children,
+            source: None,
         })
     }
 
@@ -130,201 +320,3958 @@ impl Netscape {
     /// assert_eq!(netscape.title, "Collection Title");
     /// ```
     pub fn from_html(raw: &str) -> Result<Self, Error> {
-        let node = parse_html().one(raw);
-        Netscape::from_node(&node)
+        Netscape::from_html_with(raw, &ParseOptions::default())
     }
 
-    /// Renders the [Netscape] model as a HTML string.
-    pub fn to_html(&self) -> Result<String, askama::Error> {
-        self.render()
-    }
+    /// Creates a [Netscape] model from a raw HTML string, customized by the [ParseOptions].
+    pub fn from_html_with(raw: &str, options: &ParseOptions) -> Result<Self, Error> {
+        let node = parse_document(raw);
+        let netscape = Netscape::from_node_with(&node, options)?;
 
-    /// Renders the [Netscape] model as a JSON representation.
-    pub fn to_json(&self) -> Result<String, serde_json::Error> {
-        serde_json::to_string(self)
+        #[cfg(feature = "render")]
+        let netscape = if options.preserve_whitespace {
+            let rendered = netscape
+                .render_escaped(EscapePolicy::default())
+                .map_err(Error::other)?;
+
+            Netscape {
+                source: Some(PreservedSource::new(
+                    &declare_utf8(raw, &netscape.charset),
+                    rendered,
+                )),
+                ..netscape
+            }
+        } else {
+            netscape
+        };
+
+        Ok(netscape)
     }
 
-    /// Gets all nested [Bookmark]s of the document
+    /// Creates a [Netscape] model from a raw HTML string, and reports the progress of the building of its items.
+    /// The HTML is parsed at once, then `on_progress` is called with the count of the parsed items
+    /// every [PROGRESS_INTERVAL] items, and once at the end.
     ///
     /// ```rust
     /// use bookmarkt::Netscape;
-    /// use std::path::Path;
+    /// use std::fs;
     ///
-    /// let path = Path::new("./res/chromium.html");
-    /// let chromium = Netscape::from_file(path).unwrap();
+    /// let raw = fs::read_to_string("./res/chromium.html").unwrap();
+    /// let mut reported = vec![];
+    /// Netscape::from_html_with_progress(&raw, |count| reported.push(count)).unwrap();
     ///
-    /// assert_eq!(chromium.get_bookmarks().len(), 6);
+    /// assert_eq!(reported, vec![9]);
     /// ```
-    pub fn get_bookmarks(&self) -> Vec<&Bookmark> {
-        self.children.shortcuts()
+    ///
+    /// [PROGRESS_INTERVAL]: Netscape::PROGRESS_INTERVAL
+    pub fn from_html_with_progress<F: FnMut(usize)>(
+        raw: &str,
+        mut on_progress: F,
+    ) -> Result<Self, Error> {
+        let node = parse_document(raw);
+        let mut count = 0;
+
+        let netscape = Netscape::from_node_observed(&node, &ParseOptions::default(), &mut || {
+            count += 1;
+
+            if count % Netscape::PROGRESS_INTERVAL == 0 {
+                on_progress(count);
+            }
+        });
+
+        if count == 0 || count % Netscape::PROGRESS_INTERVAL != 0 {
+            on_progress(count);
+        }
+
+        netscape
     }
 
-    /// Gets all nested [Folder]s of the document
+    /// Guesses the browser that exported a raw HTML string, from the markers it left in the document.
+    /// It is a heuristic that can be used to apply some browser-specific normalizations.
+    ///
+    /// ```rust
+    /// use bookmarkt::{BrowserHint, Netscape};
+    /// use std::fs;
+    ///
+    /// let raw = fs::read_to_string("./res/firefox.html").unwrap();
+    ///
+    /// assert_eq!(Netscape::detect_source(&raw), BrowserHint::Firefox);
+    /// ```
+    pub fn detect_source(raw: &str) -> BrowserHint {
+        detect_source(raw)
+    }
+
+    /// Imports the bookmarks of a Firefox profile from its `places.sqlite` database, without exporting them first.
+    /// The folder hierarchy is rebuilt from the `moz_bookmarks` table and the urls are read from the `moz_places` table.
+    ///
+    /// The database is opened read-only. It requires the `sqlite` feature.
+    /// A running Firefox locks the database, a copy of the file should be read instead.
+    #[cfg(feature = "sqlite")]
+    pub fn from_places_sqlite(path: &Path) -> Result<Self, Error> {
+        crate::places::from_places_sqlite(path)
+    }
+
+    /// Creates a [Netscape] model from the `Bookmarks` JSON file of a Chrome profile, as in the Chrome exports :
+    /// the `bookmark_bar` root is the toolbar [Folder] and the `other` bookmarks are imported at the root.
+    /// The dates, in microseconds since 1601, are converted into UNIX seconds.
+    /// It fails when the document isn't JSON or has no `roots`.
     ///
     /// ```rust
     /// use bookmarkt::Netscape;
-    /// use std::path::Path;
     ///
-    /// let path = Path::new("./res/chromium.html");
-    /// let chromium = Netscape::from_file(path).unwrap();
+    /// let raw = r#"{"roots": {"bookmark_bar": {"type": "folder", "name": "Bookmarks bar", "children": [
+    ///     {"type": "url", "name": "Rust", "url": "https://www.rust-lang.org/", "date_added": "13245384526000000"}
+    /// ]}}}"#;
+    /// let chrome = Netscape::from_chrome_json(raw).unwrap();
     ///
-    /// assert_eq!(chromium.get_folders().len(), 3);
+    /// assert!(chrome.toolbar_folder().is_some());
+    /// assert_eq!(chrome.get_bookmarks()[0].add_date, "1600910926");
     /// ```
-    pub fn get_folders(&self) -> Vec<&Folder> {
-        self.children.subfolders()
+    pub fn from_chrome_json(raw: &str) -> Result<Self, Error> {
+        from_chrome_json(raw)
     }
-}
 
-impl PartialEq for Netscape {
-    fn eq(&self, other: &Self) -> bool {
-        self.title == other.title && self.h1 == other.h1 && self.children == other.children
+    /// Creates a [Netscape] model from a Pocket HTML export, its flat list of links become [Bookmark]s
+    /// with their `time_added` as `add_date`. It fails when the document has no Pocket list of links.
+    ///
+    /// When `group_by_tags` is set, every bookmark is copied into a [Folder] per tag, and the untagged ones stay at the root.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    ///
+    /// let raw = r#"<ul><li><a href="https://www.rust-lang.org/" time_added="1600910526" tags="rust">Rust</a></li></ul>"#;
+    /// let pocket = Netscape::from_pocket(raw, true).unwrap();
+    ///
+    /// assert_eq!(pocket.get_folders()[0].title, "rust");
+    /// assert_eq!(pocket.get_bookmarks()[0].add_date, "1600910526");
+    /// ```
+    pub fn from_pocket(raw: &str, group_by_tags: bool) -> Result<Self, Error> {
+        from_pocket(raw, group_by_tags)
     }
-}
 
-#[allow(dead_code)]
-fn sanitize_file(path: &Path) -> String {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
+    /// Extracts all the [Bookmark]s of a raw HTML string, the folder structure is ignored.
+    /// It is faster than parsing the whole document when only the links are needed.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::fs;
+    ///
+    /// let raw = fs::read_to_string("./res/chromium.html").unwrap();
+    ///
+    /// assert_eq!(Netscape::extract_bookmarks(&raw).len(), 6);
+    /// ```
+    pub fn extract_bookmarks(raw: &str) -> Vec<Bookmark> {
+        parse_document(raw)
+            .select_elements("A")
+            .iter()
+            .filter_map(Bookmark::from_node)
+            .collect()
+    }
 
-    let file = File::open(path).unwrap();
-    let mut contents = String::new();
+    /// Creates a flat [Netscape] model from a list of `(title, href)` links.
+    /// All the [Bookmark]s are stored at the root of the document.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    ///
+    /// let links = vec![(String::from("Kernel"), String::from("https://www.kernel.org/"))];
+    /// let netscape = Netscape::from_links("Links", links);
+    ///
+    /// assert_eq!(netscape.title, "Links");
+    /// assert_eq!(netscape.get_bookmarks()[0].href, "https://www.kernel.org/");
+    /// ```
+    pub fn from_links(title: &str, links: impl IntoIterator<Item = (String, String)>) -> Self {
+        let children = links
+            .into_iter()
+            .map(|link| Item::Shortcut(Bookmark::from(link)))
+            .collect();
 
-    for line in BufReader::new(file).lines() {
-        if let Ok(content) = line {
-            contents.push_str(content.trim());
+        Netscape {
+            title: String::from(title),
+            h1: String::from(title),
+            add_date: String::new(),
+            last_modified: String::new(),
+            charset: String::new(),
+            metadata: HashMap::new(),
+            children,
+            source: None,
         }
     }
 
-    contents
-}
+    /// Creates a flat [Netscape] model from a text list of urls, one per line, optionally as `title | url`.
+    /// The title is the url itself when it is missing, the blank lines and the `#` comments are skipped.
+    /// A line is split on the first `|` followed by a valid url, the other `|` belong to the title or to the url.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    ///
+    /// let list = "# Reading list\nhttps://www.kernel.org/\n\nRust | https://www.rust-lang.org/\n";
+    /// let netscape = Netscape::from_url_list(list);
+    /// let bookmarks = netscape.get_bookmarks();
+    ///
+    /// assert_eq!(bookmarks.len(), 2);
+    /// assert_eq!(bookmarks[0].title, "https://www.kernel.org/");
+    /// assert_eq!(bookmarks[1].title, "Rust");
+    /// assert_eq!(bookmarks[1].href, "https://www.rust-lang.org/");
+    /// ```
+    pub fn from_url_list(text: &str) -> Self {
+        let links = text.lines().filter_map(|line| {
+            let line = line.trim();
 
-#[allow(dead_code)]
-fn sanitize_string(string: String) -> String {
-    let mut contents = String::new();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
 
-    for line in string.lines() {
-        contents.push_str(line.trim());
-    }
+            let (title, href) = line
+                .match_indices('|')
+                .map(|(index, _)| (line[..index].trim(), line[index + 1..].trim()))
+                .find(|(_, href)| Url::parse(href).is_ok())
+                .unwrap_or(("", line));
 
-    contents
-}
+            if href.is_empty() {
+                None
+            } else if title.is_empty() {
+                Some((href.to_string(), href.to_string()))
+            } else {
+                Some((title.to_string(), href.to_string()))
+            }
+        });
 
-#[test]
-fn should_parse_netscape_header() {
-    let html = r"
-<!DOCTYPE NETSCAPE-Bookmark-file-1>
-    <!--This is an automatically generated file.
-    It will be read and overwritten.
-    Do Not Edit! -->
-    <Title>Collection Title</Title>
-    <H1>Collection Head</H1>";
-    let netscape = Netscape::from_html(html).unwrap();
+        Netscape::from_links("Bookmarks", links)
+    }
 
-    assert_eq!(netscape.title, "Collection Title");
-    assert_eq!(netscape.h1, "Collection Head");
-}
+    /// Renders the [Netscape] model as a HTML string, with the default [RenderOptions].
+    #[cfg(feature = "render")]
+    pub fn to_html(&self) -> Result<String, askama::Error> {
+        self.to_html_with(&RenderOptions::default())
+    }
 
-#[test]
-fn should_parse_netscape_file() {
-    use crate::bookmark::BookmarkBuilder;
+    /// Renders the [Netscape] model as a HTML string customized by the [RenderOptions].
+    ///
+    /// When the document keeps its `source`, its unchanged lines are copied as they were parsed:
+    /// the options only apply on the lines rendered again, e.g. the edited items.
+    ///
+    /// ```rust
+    /// use bookmarkt::{LineEnding, Netscape, RenderOptions};
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/netscape.html");
+    /// let netscape = Netscape::from_file(path).unwrap();
+    /// let options = RenderOptions {
+    ///     line_ending: LineEnding::CrLf,
+    ///     ..RenderOptions::default()
+    /// };
+    ///
+    /// assert!(netscape.to_html_with(&options).unwrap().contains("</TITLE>\r\n"));
+    /// ```
+    #[cfg(feature = "render")]
+    pub fn to_html_with(&self, options: &RenderOptions) -> Result<String, askama::Error> {
+        let ordered = with_attribute_order(&options.attribute_order, || {
+            self.render_escaped(options.escape_policy)
+        })?;
 
-    let path = Path::new("./res/netscape.html");
-    let label = String::from("Bookmarks");
+        Ok(match &self.source {
+            Some(source) if options.escape_policy == EscapePolicy::default() => {
+                let rendered = if options.attribute_order.is_empty() {
+                    ordered.clone()
+                } else {
+                    self.render_escaped(options.escape_policy)?
+                };
 
-    let b1 = BookmarkBuilder::default()
-        .href("https://framasoft.org/")
-        .add_date("1466009059")
-        .title("Framasoft ~ Page portail du réseau")
-        .build()
-        .unwrap();
+                source.apply(&rendered, &ordered, options)
+            }
+            _ => options.apply(&ordered),
+        })
+    }
 
-    let b2 = BookmarkBuilder::default()
-        .href("https://www.kernel.org/")
-        .add_date("1466009167")
-        .title("The Linux Kernel Archives")
-        .build()
-        .unwrap();
+    /// Renders the document escaped by the `policy`, before the [RenderOptions] are applied on its lines.
+    #[cfg(feature = "render")]
+    fn render_escaped(&self, policy: EscapePolicy) -> Result<String, askama::Error> {
+        escape_document(self, policy).render()
+    }
 
-    let netscape = Netscape {
-        title: label.clone(),
-        h1: label,
-        children: vec![Item::Shortcut(b1), Item::Shortcut(b2)],
-    };
+    /// Renders the [Netscape] model as UTF-8 bytes, with the default [RenderOptions].
+    ///
+    /// When `bom` is set, the UTF-8 byte order mark `EF BB BF` is prepended for the Windows importers that expect it.
+    /// The browsers don't write it, it should be `false` otherwise.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/netscape.html");
+    /// let netscape = Netscape::from_file(path).unwrap();
+    ///
+    /// assert!(netscape.to_bytes(true).unwrap().starts_with(&[0xEF, 0xBB, 0xBF, b'<']));
+    /// assert_eq!(netscape.to_bytes(false).unwrap(), netscape.to_html().unwrap().into_bytes());
+    /// ```
+    #[cfg(feature = "render")]
+    pub fn to_bytes(&self, bom: bool) -> Result<Vec<u8>, askama::Error> {
+        let rendered = self.to_html()?;
+        let mut bytes = Vec::with_capacity(rendered.len() + 3);
 
-    assert_eq!(Netscape::from_file(path).unwrap(), netscape);
-}
+        if bom {
+            bytes.extend_from_slice(UTF8_BOM);
+        }
 
-#[test]
-fn should_serialize_json_netscape() {
-    let b1 = r#"{"href":"https://framasoft.org/","title":"Framasoft ~ Page portail du réseau","add_date":"1466009059","last_visit":"","last_modified":"","icon_uri":"","icon":""}"#;
-    let b2 = r#"{"href":"https://www.kernel.org/","title":"The Linux Kernel Archives","add_date":"1466009167","last_visit":"","last_modified":"","icon_uri":"","icon":""}"#;
+        bytes.extend_from_slice(rendered.as_bytes());
+        Ok(bytes)
+    }
 
-    let json = format!(
-        r#"{{"title":"Bookmarks","h1":"Bookmarks","children":[{},{}]}}"#,
-        b1, b2
-    );
+    /// Renders the [Netscape] model into a `writer`, without building the whole document in memory.
+    /// Every item is rendered on its own and the writer is flushed after every [Folder].
+    /// The output is the same as [Netscape::to_html], a document with a preserved `source` is rendered at once.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    /// let mut output = vec![];
+    /// chromium.write_to(&mut output).unwrap();
+    ///
+    /// assert_eq!(String::from_utf8(output).unwrap(), chromium.to_html().unwrap());
+    /// ```
+    #[cfg(feature = "render")]
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        if self.source.is_some() {
+            writer.write_all(self.to_html().map_err(Error::other)?.as_bytes())?;
+            return writer.flush();
+        }
 
-    let path = Path::new("./res/netscape.html");
-    let netscape = Netscape::from_file(path).unwrap();
+        let shell = escape_document(&self.without_children(), EscapePolicy::default());
 
-    assert_eq!(netscape.to_json().unwrap(), json)
-}
+        write_shell(
+            &mut writer,
+            &shell,
+            &self.children,
+            &RenderOptions::default(),
+        )?;
+        writer.flush()
+    }
 
-#[test]
-fn should_render_netscape_html() {
-    use crate::bookmark::BookmarkBuilder;
-    use std::fs;
+    /// Renders the [Netscape] model into the `html_path` file, and writes its metadata next to it for the tools.
+    ///
+    /// The metadata is the `<name>.meta.json` file, e.g. `bookmarks.meta.json` for `bookmarks.html`.
+    /// It stores the `export_date` (in UNIX time) and the [Netscape::stats] of the document.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::fs;
+    /// use std::path::Path;
+    ///
+    /// let chromium = Netscape::from_file(Path::new("./res/chromium.html")).unwrap();
+    /// let html_path = std::env::temp_dir().join("bookmarkt_sidecar.html");
+    /// chromium.export_with_sidecar(&html_path).unwrap();
+    ///
+    /// let metadata_path = html_path.with_file_name("bookmarkt_sidecar.meta.json");
+    /// let metadata: serde_json::Value =
+    ///     serde_json::from_str(&fs::read_to_string(&metadata_path).unwrap()).unwrap();
+    ///
+    /// assert_eq!(metadata["bookmarks"], 6);
+    /// assert_eq!(metadata["domains"]["github.com"], 2);
+    /// assert_eq!(Netscape::from_file(&html_path).unwrap(), chromium);
+    ///
+    /// fs::remove_file(html_path).unwrap();
+    /// fs::remove_file(metadata_path).unwrap();
+    /// ```
+    #[cfg(feature = "render")]
+    pub fn export_with_sidecar(&self, html_path: &Path) -> Result<(), Error> {
+        self.write_to(std::io::BufWriter::new(std::fs::File::create(html_path)?))?;
 
-    let label = String::from("Bookmarks");
-    let path = Path::new("./res/netscape.html");
+        let sidecar = ExportMetadata {
+            export_date: Utc::now().timestamp().to_string(),
+            stats: self.stats(),
+        };
 
-    let b1 = BookmarkBuilder::default()
-        .href("https://framasoft.org/")
-        .add_date("1466009059")
-        .title("Framasoft ~ Page portail du réseau")
-        .build()
-        .unwrap();
+        let stem = html_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut json = serde_json::to_string_pretty(&sidecar)?;
+        json.push('\n');
 
-    let b2 = BookmarkBuilder::default()
-        .href("https://www.kernel.org/")
-        .add_date("1466009167")
-        .title("The Linux Kernel Archives")
-        .build()
-        .unwrap();
+        std::fs::write(
+            html_path.with_file_name(format!("{}.meta.json", stem)),
+            json,
+        )
+    }
 
-    let netscape = Netscape {
-        title: label.clone(),
-        h1: label,
-        children: vec![Item::Shortcut(b1), Item::Shortcut(b2)],
-    };
+    /// Renders the [Netscape] model on a single line, without indentation and with only the non-empty attributes.
+    /// In contrast to [Netscape::to_html], it isn't meant for the browsers but for stable snapshot tests.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    ///
+    /// let links = vec![(String::from("Kernel"), String::from("https://www.kernel.org/"))];
+    /// let netscape = Netscape::from_links("Links", links);
+    ///
+    /// assert_eq!(
+    ///     netscape.to_html_compact(),
+    ///     r#"<TITLE>Links</TITLE><H1>Links</H1><DL><DT><A HREF="https://www.kernel.org/">Kernel</A></DL>"#
+    /// );
+    /// ```
+    pub fn to_html_compact(&self) -> String {
+        self.render_with(&CompactRenderer)
+    }
+
+    /// Renders the [Netscape] model as a GraphViz `digraph`, the [Folder]s and the [Bookmark]s are nodes
+    /// of distinct shapes, linked to their parent folder. The root node is the document itself.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let dot = Netscape::from_file(path).unwrap().to_dot();
+    ///
+    /// assert!(dot.starts_with("digraph bookmarks {\n"));
+    /// assert!(dot.contains("n1 [shape=folder, label=\"Bookmarks bar\"];"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        to_dot(self)
+    }
+
+    /// Renders the [Netscape] model with a custom [Renderer], instead of the bundled templates.
+    ///
+    /// ```rust
+    /// use bookmarkt::{Bookmark, Folder, Netscape, Renderer};
+    /// use std::path::Path;
+    ///
+    /// struct Markdown;
+    ///
+    /// impl Renderer for Markdown {
+    ///     fn render_bookmark(&self, bookmark: &Bookmark, depth: usize) -> String {
+    ///         format!("{}- [{}]({})\n", "  ".repeat(depth), bookmark.title, bookmark.href)
+    ///     }
+    ///
+    ///     fn render_folder(&self, folder: &Folder, children: &str, depth: usize) -> String {
+    ///         format!("{}- {}\n{}", "  ".repeat(depth), folder.title, children)
+    ///     }
+    /// }
+    ///
+    /// let path = Path::new("./res/netscape.html");
+    /// let netscape = Netscape::from_file(path).unwrap();
+    ///
+    /// assert!(netscape
+    ///     .render_with(&Markdown)
+    ///     .starts_with("- [Framasoft ~ Page portail du réseau](https://framasoft.org/)\n"));
+    /// ```
+    pub fn render_with(&self, renderer: &dyn Renderer) -> String {
+        let children = render_children(renderer, &self.children, 0);
+        renderer.render_document(self, &children)
+    }
+
+    /// Renders a self-contained HTML5 page to browse the [Netscape] model, the [Folder]s are collapsible
+    /// `<details/>` and the [Bookmark]s are links. Like [Netscape::to_html_table], it can't be imported.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let page = Netscape::from_file(path).unwrap().to_interactive_html();
+    ///
+    /// assert!(page.starts_with("<!DOCTYPE html>"));
+    /// assert!(page.contains("<summary>Bookmarks bar</summary>"));
+    /// ```
+    pub fn to_interactive_html(&self) -> String {
+        self.render_with(&InteractiveRenderer)
+    }
+
+    /// Renders a standalone HTML `<table/>` that lists the title, the folder path and the add date of every [Bookmark].
+    /// In contrast to [Netscape::to_html], it is a human-browsable report that can't be imported.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    /// let table = chromium.to_html_table();
+    ///
+    /// assert!(table.starts_with("<table>"));
+    /// assert!(table.contains("<td>Bookmarks bar</td>"));
+    /// ```
+    pub fn to_html_table(&self) -> String {
+        let mut table = String::from("<table>\n");
+        table.push_str("<thead><tr><th>Title</th><th>Folder</th><th>Added</th></tr></thead>\n");
+        table.push_str("<tbody>\n");
+
+        for (path, b) in self.children.shortcuts_with_path() {
+            let added = b
+                .added()
+                .map(|date| date.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| b.add_date.clone());
+
+            table.push_str(&format!(
+                "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&b.href),
+                escape_html(&b.title),
+                escape_html(&path.join(" / ")),
+                escape_html(&added),
+            ));
+        }
+
+        table.push_str("</tbody>\n</table>\n");
+        table
+    }
+
+    /// Gets the `scheme://host/favicon.ico` url of the domain of every [Bookmark] without `icon`, in the document order.
+    /// The bookmarks whose `href` can't be parsed or has no host are skipped. The urls aren't deduplicated.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/netscape.html");
+    /// let netscape = Netscape::from_file(path).unwrap();
+    /// let targets = netscape.favicon_targets();
+    ///
+    /// assert_eq!(targets.len(), 2);
+    /// assert!(targets.iter().all(|(url, _)| url.ends_with("/favicon.ico")));
+    /// ```
+    pub fn favicon_targets(&self) -> Vec<(String, &Bookmark)> {
+        self.children
+            .shortcuts()
+            .into_iter()
+            .filter(|b| b.icon.is_empty())
+            .filter_map(|b| favicon_url(&b.href).map(|url| (url, b)))
+            .collect()
+    }
+
+    /// Fetches the `/favicon.ico` of the domain of every [Bookmark] without `icon`, and inlines it as a `data:` URI.
+    ///
+    /// Every favicon is fetched once per domain, with the concurrency limit and the timeout of the [FaviconOptions].
+    /// The failed requests are silently skipped. It requires the `online` feature and a [tokio] runtime.
+    ///
+    /// [FaviconOptions]: crate::FaviconOptions
+    /// [tokio]: https://tokio.rs
+    #[cfg(feature = "online")]
+    pub async fn fetch_favicons_async(&mut self, options: &crate::FaviconOptions) {
+        use crate::favicon::fetch_data_uri;
+        use futures::stream::{self, StreamExt};
+
+        let client = match reqwest::Client::builder().timeout(options.timeout).build() {
+            Ok(client) => client,
+            Err(_) => return,
+        };
+
+        let mut urls: Vec<String> = self
+            .favicon_targets()
+            .into_iter()
+            .map(|(url, _)| url)
+            .collect();
+        urls.sort();
+        urls.dedup();
+
+        let icons: HashMap<String, String> = stream::iter(urls)
+            .map(|url| {
+                let client = &client;
+                async move { fetch_data_uri(client, &url).await.map(|icon| (url, icon)) }
+            })
+            .buffer_unordered(options.concurrency.max(1))
+            .filter_map(|fetched| async move { fetched })
+            .collect()
+            .await;
+
+        for bookmark in self.bookmarks_mut().filter(|b| b.icon.is_empty()) {
+            if let Some(icon) = favicon_url(&bookmark.href).and_then(|url| icons.get(&url)) {
+                bookmark.icon = icon.clone();
+            }
+        }
+    }
+
+    /// Checks that the document survives an export : it is rendered, parsed back and compared.
+    /// On mismatch, the error describes the first differing field and its path of child indexes.
+    ///
+    /// The `charset` isn't compared, the documents are always rendered as `UTF-8`.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/firefox.html");
+    /// let firefox = Netscape::from_file(path).unwrap();
+    ///
+    /// assert_eq!(firefox.roundtrip_check(), Ok(()));
+    /// ```
+    #[cfg(feature = "render")]
+    pub fn roundtrip_check(&self) -> Result<(), String> {
+        let rendered = self.to_html().map_err(|e| e.to_string())?;
+        let parsed = Netscape::from_html(&rendered).map_err(|e| e.to_string())?;
+
+        let difference = field_difference(&[], "title", &self.title, &parsed.title)
+            .or_else(|| field_difference(&[], "h1", &self.h1, &parsed.h1))
+            .or_else(|| field_difference(&[], "add_date", &self.add_date, &parsed.add_date))
+            .or_else(|| {
+                field_difference(
+                    &[],
+                    "last_modified",
+                    &self.last_modified,
+                    &parsed.last_modified,
+                )
+            })
+            .or_else(|| first_difference(&self.children, &parsed.children, &mut vec![]));
+
+        match difference {
+            Some(description) => Err(description),
+            None => Ok(()),
+        }
+    }
+
+    /// Estimates the byte size of the [Netscape::to_html] output without rendering the document.
+    /// The estimate sums the lengths of the attributes and the markup of every item, it can be used to pre-allocate a buffer.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    /// let rendered = chromium.to_html().unwrap();
+    ///
+    /// assert!(chromium.estimated_size() >= rendered.len() * 9 / 10);
+    /// ```
+    #[cfg(feature = "render")]
+    pub fn estimated_size(&self) -> usize {
+        HEADER_SIZE
+            + self.title.len()
+            + self.h1.len()
+            + attribute_size("ADD_DATE", &self.add_date)
+            + attribute_size("LAST_MODIFIED", &self.last_modified)
+            + if self.metadata.is_empty() {
+                0
+            } else {
+                1 + self.metadata_comment().len()
+            }
+            + estimate_children(&self.children)
+    }
+
+    /// Appends a [Bookmark] at the end of a folder of a rendered file, found by its titles `path_in_tree`.
+    /// The file isn't parsed : the closing `</DL>` of the folder is located textually and the rendered
+    /// bookmark is inserted before it. An empty path appends it to the root of the document.
+    ///
+    /// It fails with [ErrorKind::NotFound] when the folder isn't in the file, the missing folders aren't created.
+    ///
+    /// ```rust
+    /// use bookmarkt::{Bookmark, Netscape};
+    /// use std::path::Path;
+    ///
+    /// let copy = std::env::temp_dir().join("bookmarkt_append_bookmark.html");
+    /// std::fs::copy("./res/chromium.html", &copy).unwrap();
+    ///
+    /// let bookmark = Bookmark::from(("Rust", "https://www.rust-lang.org/"));
+    /// Netscape::append_bookmark_to_file(&copy, &["References"], &bookmark).unwrap();
+    ///
+    /// let chromium = Netscape::from_file(&copy).unwrap();
+    /// assert_eq!(chromium.path_of(&bookmark), Some(vec![String::from("References")]));
+    /// assert_eq!(chromium.get_bookmarks().len(), 7);
+    /// # std::fs::remove_file(&copy).unwrap();
+    /// ```
+    ///
+    /// [ErrorKind::NotFound]: std::io::ErrorKind::NotFound
+    #[cfg(feature = "render")]
+    pub fn append_bookmark_to_file(
+        path: &Path,
+        path_in_tree: &[&str],
+        bookmark: &Bookmark,
+    ) -> Result<(), Error> {
+        append_bookmark_to_file(path, path_in_tree, bookmark)
+    }
+
+    /// Exports the document as a tree of directories and files under `root`, which is created when missing.
+    ///
+    /// Every folder becomes a directory and every bookmark a JSON file, the titles are sanitized into valid file names
+    /// and a ` (n)` suffix disambiguates the collisions. The attributes of the folders, the header of the document and
+    /// the order of the entries are kept in hidden files, so the tree can be stored in a version control system.
+    /// Exporting again into the same `root` removes the entries of the previous export that are gone from the document.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let chromium = Netscape::from_file(Path::new("./res/chromium.html")).unwrap();
+    /// let root = std::env::temp_dir().join("bookmarkt_export_tree");
+    /// # let _ = std::fs::remove_dir_all(&root);
+    ///
+    /// chromium.export_tree(&root).unwrap();
+    ///
+    /// assert!(root.join("Bookmarks bar").is_dir());
+    /// assert!(root.join(".netscape.json").is_file());
+    /// # std::fs::remove_dir_all(&root).unwrap();
+    /// ```
+    pub fn export_tree(&self, root: &Path) -> Result<(), Error> {
+        export_tree(self, root)
+    }
+
+    /// Imports a document from a tree of directories and files under `root`, as written by [Netscape::export_tree].
+    ///
+    /// The subdirectories become folders, the JSON and `.url` files become bookmarks and the other files are ignored.
+    /// A directory with an `.order` file imports only the entries it lists, the others are ignored.
+    /// A directory without one, e.g. written by hand, imports all its entries in the order of their names.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let chromium = Netscape::from_file(Path::new("./res/chromium.html")).unwrap();
+    /// let root = std::env::temp_dir().join("bookmarkt_from_directory");
+    /// # let _ = std::fs::remove_dir_all(&root);
+    ///
+    /// chromium.export_tree(&root).unwrap();
+    ///
+    /// assert_eq!(Netscape::from_directory(&root).unwrap(), chromium);
+    /// # std::fs::remove_dir_all(&root).unwrap();
+    /// ```
+    pub fn from_directory(root: &Path) -> Result<Self, Error> {
+        import_tree(root)
+    }
+
+    /// Renders the [Netscape] model as a JSON representation.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Gets all nested [Bookmark]s of the document
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    ///
+    /// assert_eq!(chromium.get_bookmarks().len(), 6);
+    /// ```
+    pub fn get_bookmarks(&self) -> Vec<&Bookmark> {
+        self.children.shortcuts()
+    }
+
+    /// Folds all nested [Bookmark]s of the document with their folder titles path, in the document order.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium_nested.html");
+    /// let nested = Netscape::from_file(path).unwrap();
+    /// let deepest = nested.fold(0, |depth, _, path| depth.max(path.len()));
+    ///
+    /// assert_eq!(deepest, 3);
+    /// ```
+    pub fn fold<B, F: FnMut(B, &Bookmark, &[&str]) -> B>(&self, init: B, mut f: F) -> B {
+        self.children
+            .shortcuts_with_path()
+            .into_iter()
+            .fold(init, |acc, (path, bookmark)| f(acc, bookmark, &path))
+    }
+
+    /// Consumes the document and yields all its nested [Bookmark]s by value, in the document order.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    /// let hrefs: Vec<String> = chromium.into_bookmarks().map(|b| b.href).collect();
+    ///
+    /// assert_eq!(hrefs.len(), 6);
+    /// assert_eq!(hrefs[0], "https://www.mozilla.org/en-CA/firefox/");
+    /// ```
+    pub fn into_bookmarks(self) -> impl Iterator<Item = Bookmark> {
+        IntoNested::new(self.children, false).filter_map(|item| match item {
+            Item::Shortcut(b) => Some(b),
+            Item::Subfolder(_) => None,
+        })
+    }
+
+    /// Consumes the document and yields all its nested [Folder]s by value, in the document order.
+    /// Every folder keeps its direct [Bookmark]s, its subfolders are yielded after it.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium_nested.html");
+    /// let nested = Netscape::from_file(path).unwrap();
+    /// let folders: Vec<(String, usize)> = nested
+    ///     .into_folders()
+    ///     .map(|f| (f.title, f.children.len()))
+    ///     .collect();
+    ///
+    /// assert_eq!(folders[0], (String::from("Bookmarks bar"), 1));
+    /// assert_eq!(folders[1], (String::from("Rust"), 1));
+    /// ```
+    pub fn into_folders(self) -> impl Iterator<Item = Folder> {
+        IntoNested::new(self.children, true).filter_map(|item| match item {
+            Item::Subfolder(f) => Some(f),
+            Item::Shortcut(_) => None,
+        })
+    }
+
+    /// Iterates over all nested [Bookmark]s of the document as mutable references
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let mut chromium = Netscape::from_file(path).unwrap();
+    ///
+    /// for bookmark in chromium.bookmarks_mut() {
+    ///     bookmark.icon.clear();
+    /// }
+    ///
+    /// assert!(chromium.get_bookmarks().iter().all(|b| b.icon.is_empty()));
+    /// ```
+    pub fn bookmarks_mut(&mut self) -> impl Iterator<Item = &mut Bookmark> {
+        self.children.shortcuts_mut().into_iter()
+    }
+
+    /// Removes all nested [Bookmark]s whose host is one of the `domains` or one of their subdomains,
+    /// and returns how many were removed. The bookmarks with a malformed `href` are kept.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let mut chromium = Netscape::from_file(path).unwrap();
+    ///
+    /// assert_eq!(chromium.remove_domains(&["github.com", "mozilla.org"]), 3);
+    /// assert_eq!(chromium.get_bookmarks().len(), 3);
+    /// ```
+    pub fn remove_domains(&mut self, domains: &[&str]) -> usize {
+        let matches = |b: &Bookmark| {
+            Url::parse(&b.href).is_ok_and(|url| {
+                url.host_str().is_some_and(|host| {
+                    let host = host.to_ascii_lowercase();
+
+                    domains.iter().any(|domain| {
+                        let domain = domain.to_ascii_lowercase();
+                        host == domain || host.ends_with(&format!(".{}", domain))
+                    })
+                })
+            })
+        };
+
+        self.children.drain_shortcuts(&matches).len()
+    }
+
+    /// Removes the named query parameters from the `href` of every [Bookmark], a name ending with `*` is a prefix.
+    /// With no `params`, the tracking parameters `utm_*`, `fbclid` and `gclid` are removed.
+    /// The rest of the urls is left intact, the malformed urls are skipped.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    ///
+    /// let links = vec![(
+    ///     String::from("Kernel"),
+    ///     String::from("https://www.kernel.org/?utm_source=feed&utm_medium=rss&lang=en"),
+    /// )];
+    /// let mut netscape = Netscape::from_links("Links", links);
+    /// netscape.strip_tracking_params(&[]);
+    ///
+    /// assert_eq!(netscape.get_bookmarks()[0].href, "https://www.kernel.org/?lang=en");
+    /// ```
+    pub fn strip_tracking_params(&mut self, params: &[&str]) {
+        let params = if params.is_empty() {
+            &TRACKING_PARAMS[..]
+        } else {
+            params
+        };
+
+        for bookmark in self.bookmarks_mut() {
+            if let Some(href) = strip_query_params(&bookmark.href, params) {
+                bookmark.href = href;
+            }
+        }
+    }
+
+    /// Removes the [Bookmark]s sharing the same `href`, and returns how many were removed.
+    ///
+    /// Among the duplicates, the richest one is kept at its position : it is the one with the most non-empty
+    /// `title`, `description`, `icon`, `icon_uri`, `shortcut_url`, `add_date`, `last_visit` and `last_modified`,
+    /// the first one in the document order on a tie. Its empty fields are then filled by the other duplicates, in the document order.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let mut chromium = Netscape::from_file(path).unwrap();
+    /// chromium.children.extend(Netscape::from_file(path).unwrap().children);
+    ///
+    /// assert_eq!(chromium.dedup_richest(), 6);
+    /// assert_eq!(chromium.get_bookmarks().len(), 6);
+    /// ```
+    pub fn dedup_richest(&mut self) -> usize {
+        let mut bookmarks = self.children.shortcuts_mut();
+        let mut groups: Vec<Vec<usize>> = vec![];
+        let mut indexes: HashMap<String, usize> = HashMap::new();
+
+        for (i, bookmark) in bookmarks.iter().enumerate() {
+            match indexes.get(&bookmark.href) {
+                Some(&group) => groups[group].push(i),
+                None => {
+                    indexes.insert(bookmark.href.clone(), groups.len());
+                    groups.push(vec![i]);
+                }
+            }
+        }
+
+        let mut removed = vec![false; bookmarks.len()];
+
+        for group in groups.iter().filter(|group| group.len() > 1) {
+            let mut richest = group[0];
+
+            for &i in group.iter() {
+                if richness(bookmarks[i]) > richness(bookmarks[richest]) {
+                    richest = i;
+                }
+            }
+
+            for &i in group.iter().filter(|&&i| i != richest) {
+                let duplicate = bookmarks[i].clone();
+                fill_empty_fields(bookmarks[richest], &duplicate);
+                removed[i] = true;
+            }
+        }
+
+        drop(bookmarks);
+
+        let mut index = 0;
+        retain_shortcuts(&mut self.children, &mut index, &removed);
+
+        removed.iter().filter(|r| **r).count()
+    }
+
+    /// Describes the changes of a `mutation` without applying them, i.e. a dry run of any mutating method.
+    /// The mutation is applied to a copy of the document, then the copy is compared with the document.
+    /// The destructive bulk operations have their own shortcut, [Netscape::remove_domains_preview] and
+    /// [Netscape::dedup_richest_preview], the deduplication of the document.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    /// let preview = chromium.preview(|n| n.move_matching(|b| b.href.contains("askama"), &["Templates"]));
+    ///
+    /// assert_eq!(preview.added_folders, vec![vec![String::from("Templates")]]);
+    /// assert!(preview.removed_bookmarks.is_empty());
+    /// assert_eq!(preview.moved_bookmarks.len(), 1);
+    /// assert_eq!(preview.moved_bookmarks[0].1, vec![String::from("Templates")]);
+    /// assert_eq!(chromium.get_bookmarks().len(), 6);
+    /// ```
+    pub fn preview<F: FnOnce(&mut Netscape)>(&self, mutation: F) -> ChangePreview {
+        let mut copy = Netscape {
+            children: self.children.clone(),
+            ..self.without_children()
+        };
+
+        mutation(&mut copy);
+
+        ChangePreview::between(&self.children, &copy.children)
+    }
+
+    /// Describes the [Bookmark]s that [Netscape::remove_domains] would remove, without removing them.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    /// let preview = chromium.remove_domains_preview(&["github.com", "mozilla.org"]);
+    ///
+    /// assert_eq!(preview.removed_bookmarks.len(), 3);
+    /// assert!(preview.added_bookmarks.is_empty());
+    /// ```
+    pub fn remove_domains_preview(&self, domains: &[&str]) -> ChangePreview {
+        self.preview(|n| {
+            n.remove_domains(domains);
+        })
+    }
+
+    /// Describes the changes that [Netscape::dedup_richest] would apply, without applying them.
+    /// The kept duplicates whose dates are filled are reported as modified.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let mut chromium = Netscape::from_file(path).unwrap();
+    /// chromium.children.extend(Netscape::from_file(path).unwrap().children);
+    ///
+    /// assert_eq!(chromium.dedup_richest_preview().removed_bookmarks.len(), 6);
+    /// assert_eq!(chromium.get_bookmarks().len(), 12);
+    /// ```
+    pub fn dedup_richest_preview(&self) -> ChangePreview {
+        self.preview(|n| {
+            n.dedup_richest();
+        })
+    }
+
+    /// Sets the `add_date` of every [Bookmark] without a valid one to the `default` date, as epoch seconds.
+    /// The valid dates are left untouched.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use chrono::{TimeZone, Utc};
+    ///
+    /// let links = vec![(String::from("Rust"), String::from("https://www.rust-lang.org/"))];
+    /// let mut netscape = Netscape::from_links("Links", links);
+    /// netscape.fill_missing_dates(Utc.timestamp_opt(1600910474, 0).unwrap());
+    ///
+    /// assert_eq!(netscape.get_bookmarks()[0].add_date, "1600910474");
+    /// ```
+    pub fn fill_missing_dates(&mut self, default: DateTime<Utc>) {
+        for bookmark in self.bookmarks_mut() {
+            if bookmark.added().is_none() {
+                bookmark.add_date = default.timestamp().to_string();
+            }
+        }
+    }
+
+    /// Sets the empty `add_date` and `last_modified` of every [Folder] from the dates of its nested items :
+    /// the `add_date` is the oldest nested `add_date`, the `last_modified` is the newest nested date.
+    /// The folder dates that are already set are left untouched, the derived dates count for the parent folders.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    ///
+    /// let raw = r#"<DL><p>
+    ///     <DT><H3>Rust</H3>
+    ///     <DL><p>
+    ///         <DT><A HREF="https://www.rust-lang.org/" ADD_DATE="1600910474">Rust</A>
+    ///         <DT><A HREF="https://crates.io/" ADD_DATE="1600910689" LAST_MODIFIED="1600910700">crates.io</A>
+    ///     </DL><p>
+    /// </DL><p>"#;
+    /// let mut netscape = Netscape::from_html(raw).unwrap();
+    /// netscape.derive_folder_dates();
+    ///
+    /// let rust = netscape.get_folders()[0];
+    /// assert_eq!(rust.add_date, "1600910474");
+    /// assert_eq!(rust.last_modified, "1600910700");
+    /// ```
+    pub fn derive_folder_dates(&mut self) {
+        derive_folder_dates(&mut self.children);
+    }
+
+    /// Removes all nested [Bookmark]s whose `href` matches, and returns how many were removed.
+    /// The [Folder]s left empty are kept.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let mut chromium = Netscape::from_file(path).unwrap();
+    ///
+    /// assert_eq!(chromium.remove_by_href("https://github.com/djc/askama"), 1);
+    /// assert_eq!(chromium.get_bookmarks().len(), 5);
+    /// ```
+    pub fn remove_by_href(&mut self, href: &str) -> usize {
+        self.children.drain_shortcuts(&|b| b.href == href).len()
+    }
+
+    /// Moves all nested [Bookmark]s matching the predicate into the destination folder.
+    /// The destination is a path of folder titles from the root, the missing folders are created.
+    /// The moved bookmarks are appended to the destination in the document order.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let mut chromium = Netscape::from_file(path).unwrap();
+    /// chromium.move_matching(|b| b.href.starts_with("https://github.com/"), &["Code", "GitHub"]);
+    ///
+    /// let github = chromium.get_folders().into_iter().find(|f| f.title == "GitHub").unwrap();
+    /// assert_eq!(github.children.len(), 2);
+    /// assert_eq!(chromium.get_bookmarks().len(), 6);
+    /// ```
+    pub fn move_matching<F: Fn(&Bookmark) -> bool>(&mut self, pred: F, dest: &[&str]) {
+        let moved = self.children.drain_shortcuts(&pred);
+        let destination = self.children.children_at_path(dest);
+
+        destination.extend(moved.into_iter().map(Item::Shortcut));
+    }
+
+    /// Inserts the `links` into the folders named by their domain, e.g. `kernel.org` for `https://www.kernel.org/`.
+    ///
+    /// The domain folder is the first [Folder] of the tree with this title, compared case-insensitively.
+    /// It is appended to the root of the document when it is missing. The links whose url is already bookmarked,
+    /// once normalized, are skipped, as their duplicates in the `links`. The links without a host go to the root.
+    ///
+    /// ```rust
+    /// use bookmarkt::{Bookmark, Netscape};
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let mut chromium = Netscape::from_file(path).unwrap();
+    /// chromium.absorb_links(vec![
+    ///     Bookmark::from(("Kernel", "https://www.kernel.org/")),
+    ///     Bookmark::from(("Kernel docs", "https://www.kernel.org/doc/")),
+    ///     Bookmark::from(("askama", "https://github.com/djc/askama/")),
+    /// ]);
+    ///
+    /// let kernel = chromium.get_folders().into_iter().find(|f| f.title == "kernel.org").unwrap();
+    /// assert_eq!(kernel.children.len(), 2);
+    /// assert_eq!(chromium.get_bookmarks().len(), 8);
+    /// ```
+    pub fn absorb_links(&mut self, links: Vec<Bookmark>) {
+        let mut known: HashSet<String> = self
+            .get_bookmarks()
+            .iter()
+            .map(|b| normalized_url(&b.href))
+            .collect();
+
+        for link in links {
+            if !known.insert(normalized_url(&link.href)) {
+                continue;
+            }
+
+            let domain = Url::parse(&link.href).ok().and_then(|url| {
+                url.host_str().map(|host| {
+                    let host = host.to_ascii_lowercase();
+                    host.strip_prefix("www.").unwrap_or(&host).to_string()
+                })
+            });
+
+            let destination = match domain {
+                Some(domain) => {
+                    if find_folder_children(&mut self.children, &domain).is_none() {
+                        self.children.push(Item::Subfolder(Folder {
+                            title: domain.clone(),
+                            ..Folder::default()
+                        }));
+                    }
+
+                    find_folder_children(&mut self.children, &domain)
+                        .expect("the domain folder exists")
+                }
+                None => &mut self.children,
+            };
+
+            destination.push(Item::Shortcut(link));
+        }
+    }
+
+    /// Merges the sibling [Folder]s that share the same title, at every level of the document.
+    /// The merged folder takes the position and the attributes of the first one, the children are concatenated.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let mut chromium = Netscape::from_file(path).unwrap();
+    /// chromium.children.extend(Netscape::from_file(path).unwrap().children);
+    /// chromium.merge_sibling_folders();
+    ///
+    /// assert_eq!(chromium.get_folders().len(), 3);
+    /// assert_eq!(chromium.get_bookmarks().len(), 12);
+    /// ```
+    pub fn merge_sibling_folders(&mut self) {
+        merge_sibling_folders(&mut self.children);
+    }
+
+    /// Gets the [Bookmark]s whose `href` has the url `scheme`, e.g. `javascript`, compared case-insensitively.
+    /// The `href`s that can't be parsed as urls are skipped.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    ///
+    /// assert_eq!(chromium.bookmarks_by_scheme("http").len(), 1);
+    /// assert!(chromium.bookmarks_by_scheme("javascript").is_empty());
+    /// ```
+    pub fn bookmarks_by_scheme(&self, scheme: &str) -> Vec<&Bookmark> {
+        self.children
+            .shortcuts()
+            .into_iter()
+            .filter(|b| {
+                Url::parse(&b.href).is_ok_and(|url| url.scheme().eq_ignore_ascii_case(scheme))
+            })
+            .collect()
+    }
+
+    /// Gets the [Bookmark]s whose `href` is a Firefox `place:` query, e.g. the "Most Visited" smart bookmark.
+    /// They can be skipped at parse time with the `skip_place_queries` attribute of the [ParseOptions].
+    ///
+    /// ```rust
+    /// use bookmarkt::{Netscape, ParseOptions};
+    ///
+    /// let raw = r#"<DL><p>
+    ///     <DT><A HREF="place:sort=8&maxResults=10">Most Visited</A>
+    ///     <DT><A HREF="https://www.kernel.org/">Kernel</A>
+    /// </DL><p>"#;
+    /// let netscape = Netscape::from_html(raw).unwrap();
+    /// let options = ParseOptions {
+    ///     skip_place_queries: true,
+    ///     ..ParseOptions::default()
+    /// };
+    ///
+    /// assert_eq!(netscape.place_queries()[0].title, "Most Visited");
+    /// assert!(Netscape::from_html_with(raw, &options).unwrap().place_queries().is_empty());
+    /// ```
+    pub fn place_queries(&self) -> Vec<&Bookmark> {
+        self.children
+            .shortcuts()
+            .into_iter()
+            .filter(|b| is_place_query(&b.href))
+            .collect()
+    }
+
+    /// Gets the [Bookmark]s whose title is empty or blank, in the document order.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    ///
+    /// let links = vec![
+    ///     (String::from(""), String::from("https://www.rust-lang.org/learn")),
+    ///     (String::from("Kernel"), String::from("https://www.kernel.org/")),
+    /// ];
+    /// let mut netscape = Netscape::from_links("Links", links);
+    ///
+    /// assert_eq!(netscape.untitled_bookmarks().len(), 1);
+    /// assert_eq!(netscape.backfill_titles(), 1);
+    /// assert_eq!(netscape.get_bookmarks()[0].title, "www.rust-lang.org/learn");
+    /// ```
+    pub fn untitled_bookmarks(&self) -> Vec<&Bookmark> {
+        self.children
+            .shortcuts()
+            .into_iter()
+            .filter(|b| b.title.trim().is_empty())
+            .collect()
+    }
+
+    /// Sets the title of the untitled [Bookmark]s to the host and the path of their url, e.g. `www.rust-lang.org/learn`.
+    /// The urls without a host, or that can't be parsed, are used verbatim. It returns the number of filled titles.
+    ///
+    /// See [Netscape::untitled_bookmarks] for the bookmarks that are filled.
+    pub fn backfill_titles(&mut self) -> usize {
+        let mut count = 0;
+
+        for bookmark in self.bookmarks_mut() {
+            if bookmark.title.trim().is_empty() {
+                bookmark.title = readable_href(&bookmark.href);
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    /// Repairs the titles of the document, the [Folder]s and the [Bookmark]s that were double-encoded,
+    /// i.e. UTF-8 titles read as Windows-1252 such as `CafÃ©` for `Café`. It returns the number of repaired titles.
+    ///
+    /// The repair is heuristic : a title is only rewritten when its reinterpreted bytes are valid UTF-8
+    /// and restore some multibyte characters, the other titles are kept as is.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    ///
+    /// let links = vec![(String::from("CafÃ©"), String::from("url"))];
+    /// let mut netscape = Netscape::from_links("Links", links);
+    ///
+    /// assert_eq!(netscape.fix_mojibake(), 1);
+    /// assert_eq!(netscape.get_bookmarks()[0].title, "Café");
+    /// ```
+    pub fn fix_mojibake(&mut self) -> usize {
+        let mut count = fix_mojibake(&mut self.children);
+
+        for title in [&mut self.title, &mut self.h1] {
+            if let Some(repaired) = repair_mojibake(title) {
+                *title = repaired;
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    /// Removes the [Folder]s nested deeper than `max_depth`, the top-level folders being at depth `1`.
+    ///
+    /// The [Bookmark]s of a removed folder take its place in the deepest kept folder, and their titles are prefixed
+    /// by the path of the removed folders, e.g. `Crates / kuchiki`. The empty removed folders are dropped.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium_nested.html");
+    /// let mut nested = Netscape::from_file(path).unwrap();
+    /// nested.flatten_below(0);
+    ///
+    /// assert!(nested.get_folders().is_empty());
+    /// assert_eq!(nested.get_bookmarks()[0].title, "Bookmarks bar / Rust / Crates / kuchiki");
+    /// ```
+    pub fn flatten_below(&mut self, max_depth: usize) {
+        flatten_below(&mut self.children, max_depth, 1);
+    }
+
+    /// Shortens the titles of the [Folder]s and the [Bookmark]s longer than `max_len` characters
+    /// to their first `max_len` characters followed by an ellipsis. The characters are counted, not the bytes.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let mut chromium = Netscape::from_file(path).unwrap();
+    /// chromium.truncate_titles(8);
+    ///
+    /// assert_eq!(chromium.get_folders()[0].title, "Bookmark…");
+    /// assert_eq!(chromium.get_bookmarks()[0].title, "Firefox …");
+    /// ```
+    pub fn truncate_titles(&mut self, max_len: usize) {
+        truncate_titles(&mut self.children, max_len);
+    }
+
+    /// Wraps all the root items of the document into a single [Folder] titled `prefix`.
+    /// It namespaces a collection before merging it into another one.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let mut chromium = Netscape::from_file(path).unwrap();
+    /// chromium.prefix_folders("Chromium");
+    ///
+    /// assert_eq!(chromium.children.len(), 1);
+    /// assert_eq!(chromium.get_folders()[0].title, "Chromium");
+    /// assert_eq!(chromium.get_bookmarks().len(), 6);
+    /// ```
+    pub fn prefix_folders(&mut self, prefix: &str) {
+        let folder = Folder {
+            title: String::from(prefix),
+            children: std::mem::take(&mut self.children),
+            ..Folder::default()
+        };
+
+        self.children.push(Item::Subfolder(folder));
+    }
+
+    /// Creates a copy of the document with a top-level [Folder] per year of `add_date`, from the oldest to the newest.
+    /// The [Bookmark]s keep their document order in their year, those without a valid date are grouped in an `Undated` folder.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let years = Netscape::from_file(path).unwrap().group_by_year();
+    ///
+    /// assert_eq!(years.top_level_counts(), vec![(String::from("2020"), 6)]);
+    /// ```
+    pub fn group_by_year(&self) -> Netscape {
+        let mut years: Vec<(i32, Vec<Item>)> = vec![];
+        let mut undated = vec![];
+
+        for bookmark in self.get_bookmarks() {
+            let item = Item::Shortcut(bookmark.clone());
+
+            match bookmark.added().map(|date| date.year()) {
+                Some(year) => match years.iter_mut().find(|(y, _)| *y == year) {
+                    Some((_, children)) => children.push(item),
+                    None => years.push((year, vec![item])),
+                },
+                None => undated.push(item),
+            }
+        }
+
+        years.sort_by_key(|(year, _)| *year);
+
+        let mut children: Vec<Item> = years
+            .into_iter()
+            .map(|(year, children)| {
+                Item::Subfolder(Folder {
+                    title: year.to_string(),
+                    children,
+                    ..Folder::default()
+                })
+            })
+            .collect();
+
+        if !undated.is_empty() {
+            children.push(Item::Subfolder(Folder {
+                title: String::from("Undated"),
+                children: undated,
+                ..Folder::default()
+            }));
+        }
+
+        Netscape {
+            children,
+            ..self.without_children()
+        }
+    }
+
+    /// Sorts the [Bookmark]s of every folder by their `add_date`, from the oldest to the newest.
+    /// The bookmarks without a valid `add_date` are sorted last, the [Folder]s keep their positions.
+    /// The sort is stable : the bookmarks added at the same date keep their order.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/netscape.html");
+    /// let mut netscape = Netscape::from_file(path).unwrap();
+    /// netscape.children.reverse();
+    /// netscape.sort_by_date();
+    ///
+    /// assert_eq!(netscape.get_bookmarks()[0].add_date, "1466009059");
+    /// ```
+    pub fn sort_by_date(&mut self) {
+        self.children.sort_shortcuts_by_key(&date_key);
+    }
+
+    /// Sorts the [Item]s of every folder by the `key`, the [Folder]s and the [Bookmark]s are sorted together.
+    /// The sort is stable : the items with the same key keep their order.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::cmp::Reverse;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let mut chromium = Netscape::from_file(path).unwrap();
+    /// chromium.sort_children_by(|item| (item.is_shortcut(), item.title().to_lowercase()));
+    ///
+    /// assert_eq!(chromium.children[0].title(), "Bookmarks bar");
+    /// assert_eq!(chromium.children[1].title(), "Dependencies");
+    ///
+    /// chromium.sort_children_by(|item| Reverse(item.children().len()));
+    /// assert!(chromium.children[0].children().len() >= chromium.children[1].children().len());
+    /// ```
+    pub fn sort_children_by<K: Ord, F: Fn(&Item) -> K>(&mut self, key: F) {
+        self.children.sort_items_by_key(&key);
+    }
+
+    /// Reverses the order of the [Item]s of every folder, e.g. to list the items appended chronologically newest first.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    ///
+    /// let mut netscape = Netscape::from_url_list("https://www.kernel.org/\nhttps://www.rust-lang.org/");
+    /// netscape.reverse();
+    ///
+    /// assert_eq!(netscape.get_bookmarks()[0].href, "https://www.rust-lang.org/");
+    /// assert_eq!(netscape.get_bookmarks()[1].href, "https://www.kernel.org/");
+    /// ```
+    pub fn reverse(&mut self) {
+        reverse_children(&mut self.children);
+    }
+
+    /// Merges another document into this one, the conflicting [Bookmark]s are resolved by the [MergeStrategy].
+    ///
+    /// The folders are matched by their titles at every level : the matching folders are merged recursively,
+    /// the other ones are appended. Within a merged level, the bookmarks that share the same `href` are replaced
+    /// by the one the strategy keeps, the other bookmarks are appended in order.
+    ///
+    /// ```rust
+    /// use bookmarkt::{MergeStrategy, Netscape};
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/netscape.html");
+    /// let mut netscape = Netscape::from_file(path).unwrap();
+    /// let mut renamed = Netscape::from_file(path).unwrap();
+    /// for bookmark in renamed.bookmarks_mut() {
+    ///     bookmark.title = String::from("renamed");
+    ///     bookmark.last_modified = String::from("1600910474");
+    /// }
+    ///
+    /// netscape.merge(renamed, MergeStrategy::KeepNewer);
+    ///
+    /// assert_eq!(netscape.get_bookmarks().len(), 2);
+    /// assert!(netscape.get_bookmarks().iter().all(|b| b.title == "renamed"));
+    /// ```
+    pub fn merge(&mut self, other: Netscape, strategy: MergeStrategy) {
+        merge_children(&mut self.children, other.children, &strategy);
+    }
+
+    /// Applies the changes made in `theirs` since their common `base` onto a copy of this document,
+    /// i.e. a three-way merge. The [Bookmark]s are matched by their `href` :
+    ///
+    /// * the bookmarks added in `theirs` are appended to the folders of their path, the missing folders are created,
+    /// * the bookmarks removed in `theirs` are removed, unless this document changed them,
+    /// * the attributes changed in `theirs` are copied, e.g. the `title`, unless this document changed them too,
+    /// * the bookmarks moved in `theirs` are moved, unless this document moved them too,
+    /// * the bookmarks removed in this document but changed in `theirs` are added again,
+    /// * the folders renamed in `theirs` are renamed, a folder is renamed when a new sibling holds the same bookmarks,
+    /// * the folders removed in `theirs` are removed, unless they still hold bookmarks of this document.
+    ///
+    /// On a conflict, the changes of this document win. The header and the folder attributes are the ones of this document.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let base = Netscape::from_file(path).unwrap();
+    /// let mut ours = Netscape::from_file(path).unwrap();
+    /// let mut theirs = Netscape::from_file(path).unwrap();
+    ///
+    /// ours.remove_by_href("https://duckduckgo.com/?va=b&t=hc");
+    /// theirs.remove_by_href("https://github.com/djc/askama");
+    /// theirs.children.push(Netscape::from_url_list("https://crates.io/").children.remove(0));
+    ///
+    /// let patched = ours.patch_from(&base, &theirs);
+    /// let hrefs: Vec<&str> = patched.get_bookmarks().iter().map(|b| b.href.as_str()).collect();
+    ///
+    /// assert_eq!(hrefs.len(), 5);
+    /// assert!(hrefs.contains(&"https://crates.io/"));
+    /// assert!(!hrefs.contains(&"https://duckduckgo.com/?va=b&t=hc"));
+    /// assert!(!hrefs.contains(&"https://github.com/djc/askama"));
+    /// ```
+    pub fn patch_from(&self, base: &Netscape, theirs: &Netscape) -> Netscape {
+        Netscape {
+            children: patch_children(&self.children, &base.children, &theirs.children),
+            ..self.without_children()
+        }
+    }
+
+    /// Merges another document into this one, the [Bookmark]s of both trees are interleaved by their `add_date`.
+    ///
+    /// The folders are matched by their titles at every level : the matching folders are merged recursively,
+    /// the other ones are appended. Within a merged level, the bookmarks are sorted from the oldest to the newest,
+    /// the bookmarks of `self` come first on equal dates and the bookmarks without a valid `add_date` go last.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/netscape.html");
+    /// let mut netscape = Netscape::from_file(path).unwrap();
+    /// let mut older = Netscape::from_file(path).unwrap();
+    /// older.children.truncate(1);
+    ///
+    /// netscape.merge_by_date(older);
+    ///
+    /// let dates: Vec<&str> = netscape.get_bookmarks().iter().map(|b| b.add_date.as_str()).collect();
+    /// assert_eq!(dates, vec!["1466009059", "1466009059", "1466009167"]);
+    /// ```
+    pub fn merge_by_date(&mut self, other: Netscape) {
+        merge_children_by_date(&mut self.children, other.children);
+    }
+
+    /// Gets all nested [Folder]s of the document
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    ///
+    /// assert_eq!(chromium.get_folders().len(), 3);
+    /// ```
+    pub fn get_folders(&self) -> Vec<&Folder> {
+        self.children.subfolders()
+    }
+
+    /// Lists the `(depth, title)` of every nested [Folder] in the document order, the top-level folders have a depth of 1.
+    /// The [Bookmark]s are excluded, it is the skeleton of the tree, e.g. for a sidebar.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium_nested.html");
+    /// let nested = Netscape::from_file(path).unwrap();
+    ///
+    /// assert_eq!(
+    ///     nested.table_of_contents(),
+    ///     vec![
+    ///         (1, String::from("Bookmarks bar")),
+    ///         (2, String::from("Rust")),
+    ///         (3, String::from("Crates")),
+    ///         (2, String::from("Empty")),
+    ///     ]
+    /// );
+    /// ```
+    pub fn table_of_contents(&self) -> Vec<(usize, String)> {
+        let mut contents = vec![];
+        collect_table_of_contents(&self.children, 1, &mut contents);
+        contents
+    }
+
+    /// Gets the personal toolbar folder, e.g. the `Bookmarks bar` of Chromium, searched in the whole tree.
+    ///
+    /// The first [Folder] flagged as the personal toolbar folder takes precedence. When no folder is flagged,
+    /// it is the first folder with a conventional toolbar title : `Bookmarks Toolbar`, `Bookmarks bar`
+    /// or `Favorites bar`, compared case-insensitively.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/firefox.html");
+    /// let firefox = Netscape::from_file(path).unwrap();
+    ///
+    /// assert_eq!(firefox.toolbar_folder().unwrap().title, "Bookmarks Toolbar");
+    /// ```
+    pub fn toolbar_folder(&self) -> Option<&Folder> {
+        let folders = self.get_folders();
+
+        folders
+            .iter()
+            .find(|f| f.personal_toolbar_folder)
+            .or_else(|| {
+                folders.iter().find(|f| {
+                    TOOLBAR_TITLES
+                        .iter()
+                        .any(|title| f.title.trim().eq_ignore_ascii_case(title))
+                })
+            })
+            .copied()
+    }
+
+    /// Lists the titles of the [Folder]s that appear more than once directly inside the toolbar folder.
+    /// Some browsers key the toolbar folders by their title, and warn about the duplicates on import.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    ///
+    /// assert!(chromium.toolbar_title_conflicts().is_empty());
+    /// ```
+    pub fn toolbar_title_conflicts(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let mut conflicts = vec![];
+
+        if let Some(toolbar) = self.toolbar_folder() {
+            for child in toolbar.children.iter() {
+                if let Item::Subfolder(f) = child {
+                    if !seen.insert(f.title.as_str()) && !conflicts.contains(&f.title) {
+                        conflicts.push(f.title.clone());
+                    }
+                }
+            }
+        }
+
+        conflicts
+    }
+
+    /// Gets all nested [Bookmark]s added between the `start` and the `end` dates (inclusive).
+    /// The bookmarks without a valid `add_date` are excluded.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use chrono::{TimeZone, Utc};
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/netscape.html");
+    /// let netscape = Netscape::from_file(path).unwrap();
+    ///
+    /// let start = Utc.timestamp_opt(1466009000, 0).unwrap();
+    /// let end = Utc.timestamp_opt(1466009100, 0).unwrap();
+    ///
+    /// assert_eq!(netscape.added_between(start, end).len(), 1);
+    /// ```
+    pub fn added_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<&Bookmark> {
+        self.get_bookmarks()
+            .into_iter()
+            .filter(|b| b.added().is_some_and(|date| start <= date && date <= end))
+            .collect()
+    }
+
+    /// Gets all nested [Bookmark]s modified strictly after the `since` date, e.g. to push the changes of a sync.
+    /// The `last_modified` date of a bookmark falls back on its `add_date`, the bookmarks without both are excluded.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use chrono::{TimeZone, Utc};
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/netscape.html");
+    /// let mut netscape = Netscape::from_file(path).unwrap();
+    /// netscape.bookmarks_mut().next().unwrap().last_modified = String::from("1466009200");
+    ///
+    /// let since = Utc.timestamp_opt(1466009167, 0).unwrap();
+    /// let modified = netscape.modified_since(since);
+    ///
+    /// assert_eq!(modified.len(), 1);
+    /// assert_eq!(modified[0].href, "https://framasoft.org/");
+    /// ```
+    pub fn modified_since(&self, since: DateTime<Utc>) -> Vec<&Bookmark> {
+        self.get_bookmarks()
+            .into_iter()
+            .filter(|b| {
+                b.modified()
+                    .or_else(|| b.added())
+                    .is_some_and(|date| date > since)
+            })
+            .collect()
+    }
+
+    /// Checks the health of the document, it reports the duplicate urls, the empty titles,
+    /// the invalid urls, the empty folders and the malformed dates as [LintIssue]s.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    ///
+    /// assert!(chromium.lint().is_empty());
+    /// ```
+    pub fn lint(&self) -> Vec<LintIssue> {
+        lint(&self.children)
+    }
+
+    /// Computes a stable hash of the structure of the document, it can be used to detect changes.
+    ///
+    /// Only the folder titles, the bookmark `href`s and `title`s and their nesting are hashed :
+    /// the dates, the icons and the header are ignored. The hash depends on the children order,
+    /// the document should be sorted first to compare collections regardless of their order.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    /// let mut touched = Netscape::from_file(path).unwrap();
+    ///
+    /// for bookmark in touched.bookmarks_mut() {
+    ///     bookmark.last_visit = String::from("1600910720");
+    /// }
+    ///
+    /// assert_eq!(chromium.content_hash(), touched.content_hash());
+    /// ```
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = ContentHasher::new();
+        hash_children(&self.children, &mut hasher);
+        hasher.finish()
+    }
+
+    /// Expands a keyword search like the browsers do : the [Bookmark] whose `shortcut_url` is the `keyword`
+    /// gets the `%s` placeholders of its `href` replaced by the url-encoded `query`, and the `%S` ones by the raw `query`.
+    /// It is [None] when no bookmark has this keyword, the keywords are compared case-insensitively.
+    ///
+    /// ```rust
+    /// use bookmarkt::{Bookmark, Netscape};
+    ///
+    /// let mut netscape = Netscape::from_links("Links", vec![]);
+    /// let mut wiki = Bookmark::from(("Wikipedia", "https://en.wikipedia.org/w/index.php?search=%s"));
+    /// wiki.shortcut_url = String::from("wiki");
+    /// netscape.children.push(bookmarkt::Item::Shortcut(wiki));
+    ///
+    /// assert_eq!(
+    ///     netscape.resolve_keyword("wiki", "rust lang"),
+    ///     Some(String::from("https://en.wikipedia.org/w/index.php?search=rust+lang"))
+    /// );
+    /// assert_eq!(netscape.resolve_keyword("missing", "rust"), None);
+    /// ```
+    pub fn resolve_keyword(&self, keyword: &str, query: &str) -> Option<String> {
+        let bookmark = self
+            .get_bookmarks()
+            .into_iter()
+            .find(|b| !b.shortcut_url.is_empty() && b.shortcut_url.eq_ignore_ascii_case(keyword))?;
+
+        let encoded: String = byte_serialize(query.as_bytes()).collect();
+
+        Some(bookmark.href.replace("%s", &encoded).replace("%S", query))
+    }
+
+    /// Gets the folder titles path of a [Bookmark], it is [None] when the bookmark isn't found.
+    /// A reference obtained from the document itself is located by its address, any other bookmark
+    /// by its `href` and its `title`.
+    ///
+    /// ```rust
+    /// use bookmarkt::{Bookmark, Netscape};
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    /// let last = chromium.get_bookmarks()[5];
+    ///
+    /// assert_eq!(chromium.path_of(last), Some(vec![String::from("Dependencies")]));
+    /// assert_eq!(chromium.path_of(&Bookmark::from(("missing", "url"))), None);
+    /// ```
+    pub fn path_of(&self, bookmark: &Bookmark) -> Option<Vec<String>> {
+        let paths = self.children.shortcuts_with_path();
+
+        paths
+            .iter()
+            .find(|(_, b)| std::ptr::eq(*b, bookmark))
+            .or_else(|| {
+                paths
+                    .iter()
+                    .find(|(_, b)| b.href == bookmark.href && b.title == bookmark.title)
+            })
+            .map(|(path, _)| path.iter().map(|title| title.to_string()).collect())
+    }
+
+    /// Gets the titles path of the deepest [Folder] that contains both [Bookmark]s, found by their `href`.
+    /// The path is empty when the bookmarks only share the root, it is [None] if any bookmark is missing.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium_nested.html");
+    /// let nested = Netscape::from_file(path).unwrap();
+    /// let kuchiki = "https://github.com/kuchiki-rs/kuchiki";
+    ///
+    /// assert_eq!(
+    ///     nested.common_ancestor(kuchiki, "https://www.rust-lang.org/"),
+    ///     Some(vec![String::from("Bookmarks bar"), String::from("Rust")])
+    /// );
+    /// assert_eq!(nested.common_ancestor(kuchiki, "https://www.kernel.org/"), Some(vec![]));
+    /// assert_eq!(nested.common_ancestor(kuchiki, "missing"), None);
+    /// ```
+    pub fn common_ancestor(&self, href_a: &str, href_b: &str) -> Option<Vec<String>> {
+        let paths = self.children.shortcuts_with_path();
+        let find = |href: &str| paths.iter().find(|(_, b)| b.href == href).map(|(p, _)| p);
+
+        let path_a = find(href_a)?;
+        let path_b = find(href_b)?;
+
+        Some(
+            path_a
+                .iter()
+                .zip(path_b.iter())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a.to_string())
+                .collect(),
+        )
+    }
+
+    /// Groups the [Bookmark]s whose urls are equivalent once normalized, ignoring the case of the host,
+    /// its `www.` prefix, the default ports and the trailing slashes. Only the groups of several bookmarks are returned,
+    /// in the document order.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    ///
+    /// let links = vec![
+    ///     (String::from("Kernel"), String::from("https://www.kernel.org/")),
+    ///     (String::from("Rust"), String::from("https://www.rust-lang.org/")),
+    ///     (String::from("kernel.org"), String::from("https://Kernel.org")),
+    /// ];
+    /// let netscape = Netscape::from_links("Links", links);
+    /// let duplicates = netscape.near_duplicates();
+    ///
+    /// assert_eq!(duplicates.len(), 1);
+    /// assert_eq!(duplicates[0][1].title, "kernel.org");
+    /// ```
+    pub fn near_duplicates(&self) -> Vec<Vec<&Bookmark>> {
+        let mut indexes: HashMap<String, usize> = HashMap::new();
+        let mut groups: Vec<Vec<&Bookmark>> = vec![];
+
+        for bookmark in self.get_bookmarks() {
+            let index = *indexes
+                .entry(normalized_url(&bookmark.href))
+                .or_insert_with(|| {
+                    groups.push(vec![]);
+                    groups.len() - 1
+                });
+
+            groups[index].push(bookmark);
+        }
+
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+
+    /// Groups the [Bookmark]s that share the same title but point to different `href`s, e.g. a page that moved.
+    /// The groups are in the order of their first bookmark, the untitled bookmarks are skipped.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    ///
+    /// let links = vec![
+    ///     (String::from("Askama"), String::from("https://github.com/djc/askama")),
+    ///     (String::from("Kernel"), String::from("https://www.kernel.org/")),
+    ///     (String::from("Askama"), String::from("https://github.com/askama-rs/askama")),
+    ///     (String::from("Kernel"), String::from("https://www.kernel.org/")),
+    /// ];
+    /// let netscape = Netscape::from_links("Links", links);
+    /// let collisions = netscape.title_collisions();
+    ///
+    /// assert_eq!(collisions.len(), 1);
+    /// assert_eq!(collisions[0].0, "Askama");
+    /// assert_eq!(collisions[0].1.len(), 2);
+    /// ```
+    pub fn title_collisions(&self) -> Vec<(String, Vec<&Bookmark>)> {
+        let mut indexes: HashMap<&str, usize> = HashMap::new();
+        let mut groups: Vec<(String, Vec<&Bookmark>)> = vec![];
+
+        for bookmark in self.get_bookmarks() {
+            if bookmark.title.trim().is_empty() {
+                continue;
+            }
+
+            let index = *indexes.entry(&bookmark.title).or_insert_with(|| {
+                groups.push((bookmark.title.clone(), vec![]));
+                groups.len() - 1
+            });
+
+            groups[index].1.push(bookmark);
+        }
+
+        groups.retain(|(_, group)| group.iter().any(|b| b.href != group[0].href));
+        groups
+    }
+
+    /// Counts the `(added, removed)` [Bookmark]s since a `previous` version of the document.
+    /// The bookmarks are compared by their `href`, the duplicated urls are counted once.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let previous = Netscape::from_file(path).unwrap();
+    /// let mut current = Netscape::from_file(path).unwrap();
+    /// current.remove_by_href("https://github.com/djc/askama");
+    ///
+    /// assert_eq!(current.change_summary(&previous), (0, 1));
+    /// assert_eq!(previous.change_summary(&current), (1, 0));
+    /// ```
+    pub fn change_summary(&self, previous: &Netscape) -> (usize, usize) {
+        let current: HashSet<&str> = self
+            .get_bookmarks()
+            .iter()
+            .map(|b| b.href.as_str())
+            .collect();
+        let previous: HashSet<&str> = previous
+            .get_bookmarks()
+            .iter()
+            .map(|b| b.href.as_str())
+            .collect();
+
+        (
+            current.difference(&previous).count(),
+            previous.difference(&current).count(),
+        )
+    }
+
+    /// Counts the nested [Bookmark]s and [Folder]s of the document, and the bookmarks of every host.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let chromium = Netscape::from_file(Path::new("./res/chromium.html")).unwrap();
+    /// let stats = chromium.stats();
+    ///
+    /// assert_eq!(stats.bookmarks, 6);
+    /// assert_eq!(stats.folders, chromium.get_folders().len());
+    /// assert_eq!(stats.domains["github.com"], 2);
+    /// ```
+    pub fn stats(&self) -> Stats {
+        let bookmarks = self.get_bookmarks();
+        let mut domains = BTreeMap::new();
+
+        for bookmark in bookmarks.iter() {
+            if let Some(host) = Url::parse(&bookmark.href)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+            {
+                *domains.entry(host).or_insert(0) += 1;
+            }
+        }
+
+        Stats {
+            bookmarks: bookmarks.len(),
+            folders: self.get_folders().len(),
+            domains,
+        }
+    }
+
+    /// Counts the nested [Bookmark]s of each top-level [Folder] of the document.
+    /// The bookmarks stored at the root of the document are grouped under an empty title.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    ///
+    /// assert_eq!(
+    ///     chromium.top_level_counts(),
+    ///     vec![
+    ///         (String::from("Bookmarks bar"), 2),
+    ///         (String::from("References"), 2),
+    ///         (String::from("Dependencies"), 2),
+    ///     ]
+    /// );
+    /// ```
+    pub fn top_level_counts(&self) -> Vec<(String, usize)> {
+        let mut counts = vec![];
+        let mut loose = 0;
+
+        for item in self.children.iter() {
+            match item {
+                Item::Subfolder(f) => counts.push((f.title.clone(), f.children.shortcuts().len())),
+                Item::Shortcut(_) => loose += 1,
+            }
+        }
+
+        if loose > 0 {
+            counts.insert(0, (String::new(), loose));
+        }
+
+        counts
+    }
+
+    /// Gets the `n` [Folder]s with the most nested [Bookmark]s, with their titles path and their count.
+    /// The folders are sorted by descending count, the folders with the same count keep the document order.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium_nested.html");
+    /// let nested = Netscape::from_file(path).unwrap();
+    ///
+    /// assert_eq!(
+    ///     nested.largest_folders(2),
+    ///     vec![
+    ///         (vec![String::from("Bookmarks bar")], 3),
+    ///         (vec![String::from("Bookmarks bar"), String::from("Rust")], 2),
+    ///     ]
+    /// );
+    /// ```
+    pub fn largest_folders(&self, n: usize) -> Vec<(Vec<String>, usize)> {
+        let mut counts = vec![];
+        collect_folder_counts(&self.children, &mut vec![], &mut counts);
+
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        counts.truncate(n);
+        counts
+    }
+
+    /// Gets the paths of the [Folder]s whose title collides with the one of a sibling folder.
+    /// Each path lists the folder titles from the root of the document to the duplicated folder.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    ///
+    /// assert!(chromium.duplicate_folder_paths().is_empty());
+    /// ```
+    pub fn duplicate_folder_paths(&self) -> Vec<Vec<String>> {
+        let mut paths = vec![];
+        collect_duplicate_folders(&self.children, &mut vec![], &mut paths);
+        paths
+    }
+
+    /// Checks if the [Netscape] model holds more than `limit` [Bookmark]s, the [Folder]s aren't counted.
+    ///
+    /// The browsers, Firefox notably, have been known to stall or to fail on the import of very large files.
+    /// The `limit` is the maximum number of bookmarks per import that the caller's target browser handles well.
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    /// let count = chromium.get_bookmarks().len();
+    ///
+    /// assert!(chromium.exceeds_import_limit(count - 1));
+    /// assert!(!chromium.exceeds_import_limit(count));
+    /// ```
+    pub fn exceeds_import_limit(&self, limit: usize) -> bool {
+        self.children.shortcuts().len() > limit
+    }
+
+    /// Splits the [Netscape] model into documents of at most `limit` [Bookmark]s, that can be imported one by one.
+    ///
+    /// Every chunk copies the header of the document, and the [Folder]s of its bookmarks, in the document order.
+    /// A document within the limit is returned as a single chunk, see [Netscape::exceeds_import_limit].
+    ///
+    /// ```rust
+    /// use bookmarkt::Netscape;
+    /// use std::path::Path;
+    ///
+    /// let path = Path::new("./res/chromium.html");
+    /// let chromium = Netscape::from_file(path).unwrap();
+    /// let chunks = chromium.chunks_for_import(2);
+    ///
+    /// assert!(chunks.iter().all(|chunk| !chunk.exceeds_import_limit(2)));
+    /// assert_eq!(
+    ///     chunks.iter().map(|chunk| chunk.get_bookmarks().len()).sum::<usize>(),
+    ///     chromium.get_bookmarks().len()
+    /// );
+    /// ```
+    pub fn chunks_for_import(&self, limit: usize) -> Vec<Netscape> {
+        chunk_children(&self.children, limit)
+            .into_iter()
+            .map(|children| Netscape {
+                children,
+                ..self.without_children()
+            })
+            .collect()
+    }
+
+    /// Copies the header of the document, without its children.
+    pub(crate) fn without_children(&self) -> Self {
+        Netscape {
+            title: self.title.clone(),
+            h1: self.h1.clone(),
+            add_date: self.add_date.clone(),
+            last_modified: self.last_modified.clone(),
+            charset: self.charset.clone(),
+            metadata: self.metadata.clone(),
+            children: vec![],
+            source: None,
+        }
+    }
+
+    /// Renders the `metadata` as the JSON of a `bookmarkt-meta` comment, sorted by key.
+    /// The `--` sequences are escaped, they can't appear in a comment.
+    #[cfg(feature = "render")]
+    fn metadata_comment(&self) -> String {
+        let sorted: BTreeMap<&String, &String> = self.metadata.iter().collect();
+        let json = serde_json::to_string(&sorted).unwrap_or_default();
+
+        format!(
+            "<!-- {} {} -->",
+            METADATA_PREFIX,
+            json.replace("--", "-\\u002d")
+        )
+    }
+}
+
+/// Counts the non-empty descriptive fields of a [Bookmark], to choose the richest duplicate.
+fn richness(bookmark: &Bookmark) -> usize {
+    [
+        &bookmark.title,
+        &bookmark.description,
+        &bookmark.icon,
+        &bookmark.icon_uri,
+        &bookmark.shortcut_url,
+        &bookmark.add_date,
+        &bookmark.last_visit,
+        &bookmark.last_modified,
+    ]
+    .iter()
+    .filter(|field| !field.is_empty())
+    .count()
+}
+
+fn fill_empty_fields(kept: &mut Bookmark, duplicate: &Bookmark) {
+    let fields = [
+        (&mut kept.title, &duplicate.title),
+        (&mut kept.description, &duplicate.description),
+        (&mut kept.icon, &duplicate.icon),
+        (&mut kept.icon_uri, &duplicate.icon_uri),
+        (&mut kept.shortcut_url, &duplicate.shortcut_url),
+        (&mut kept.add_date, &duplicate.add_date),
+        (&mut kept.last_visit, &duplicate.last_visit),
+        (&mut kept.last_modified, &duplicate.last_modified),
+        (&mut kept.target, &duplicate.target),
+        (&mut kept.rel, &duplicate.rel),
+    ];
+
+    for (field, value) in fields {
+        if field.is_empty() {
+            field.clone_from(value);
+        }
+    }
+}
+
+/// Removes the [Bookmark]s flagged in `removed`, indexed in the document order.
+fn retain_shortcuts(children: &mut Vec<Item>, index: &mut usize, removed: &[bool]) {
+    children.retain_mut(|child| match child {
+        Item::Subfolder(f) => {
+            retain_shortcuts(&mut f.children, index, removed);
+            true
+        }
+        Item::Shortcut(_) => {
+            *index += 1;
+            !removed[*index - 1]
+        }
+    });
+}
+
+/// Flattens the folders of the `children` that are deeper than `max_depth`, the folders of `children` are at `depth`.
+fn flatten_below(children: &mut Vec<Item>, max_depth: usize, depth: usize) {
+    for child in std::mem::take(children) {
+        match child {
+            Item::Subfolder(mut f) if depth <= max_depth => {
+                flatten_below(&mut f.children, max_depth, depth + 1);
+                children.push(Item::Subfolder(f));
+            }
+            Item::Subfolder(f) => collapse_folder(f, "", children),
+            item => children.push(item),
+        }
+    }
+}
+
+/// Moves the nested bookmarks of a folder into `children`, their titles are prefixed by the folder path.
+fn collapse_folder(folder: Folder, prefix: &str, children: &mut Vec<Item>) {
+    let prefix = format!("{}{} / ", prefix, folder.title);
+
+    for child in folder.children {
+        match child {
+            Item::Subfolder(f) => collapse_folder(f, &prefix, children),
+            Item::Shortcut(b) => {
+                let title = format!("{}{}", prefix, b.title);
+                children.push(Item::Shortcut(b.with_title(title)));
+            }
+        }
+    }
+}
+
+/// Finds the children of the first nested [Folder] whose title matches, compared case-insensitively.
+fn find_folder_children<'a>(children: &'a mut [Item], title: &str) -> Option<&'a mut Vec<Item>> {
+    for item in children.iter_mut() {
+        if let Item::Subfolder(f) = item {
+            if f.title.eq_ignore_ascii_case(title) {
+                return Some(&mut f.children);
+            }
+
+            if let Some(found) = find_folder_children(&mut f.children, title) {
+                return Some(found);
+            }
+        }
+    }
+
+    None
+}
+
+/// Formats the host and the path of an url as a title, the trailing slash is trimmed.
+fn readable_href(href: &str) -> String {
+    match Url::parse(href) {
+        Ok(url) => match url.host_str() {
+            Some(host) => format!("{}{}", host, url.path().trim_end_matches('/')),
+            None => href.to_string(),
+        },
+        Err(_) => href.to_string(),
+    }
+}
+
+/// Sorts the [Bookmark]s by their `add_date`, the ones without a valid date go last.
+fn date_key(bookmark: &Bookmark) -> (bool, i64) {
+    match bookmark.added() {
+        Some(date) => (false, date.timestamp()),
+        None => (true, 0),
+    }
+}
+
+fn merge_children_by_date(children: &mut Vec<Item>, other: Vec<Item>) {
+    for item in other {
+        match item {
+            Item::Subfolder(f) => {
+                let matching = children.iter_mut().find_map(|item| match item {
+                    Item::Subfolder(existing) if existing.title == f.title => Some(existing),
+                    _ => None,
+                });
+
+                match matching {
+                    Some(existing) => merge_children_by_date(&mut existing.children, f.children),
+                    None => children.push(Item::Subfolder(f)),
+                }
+            }
+            item => children.push(item),
+        }
+    }
+
+    sort_level_by_key(children, &date_key);
+}
+
+fn merge_sibling_folders(children: &mut Vec<Item>) {
+    let mut merged: Vec<Item> = vec![];
+
+    for item in children.drain(..) {
+        match item {
+            Item::Subfolder(f) => {
+                let sibling = merged.iter_mut().find_map(|item| match item {
+                    Item::Subfolder(existing) if existing.title == f.title => Some(existing),
+                    _ => None,
+                });
+
+                match sibling {
+                    Some(existing) => existing.children.extend(f.children),
+                    None => merged.push(Item::Subfolder(f)),
+                }
+            }
+            item => merged.push(item),
+        }
+    }
+
+    for item in merged.iter_mut() {
+        if let Item::Subfolder(f) = item {
+            merge_sibling_folders(&mut f.children);
+        }
+    }
+
+    *children = merged;
+}
+
+/// The size of the markup of an empty document.
+#[cfg(feature = "render")]
+const HEADER_SIZE: usize = 249;
+
+/// The size of the markup of a bookmark without attributes, its indentation included.
+#[cfg(feature = "render")]
+const BOOKMARK_SIZE: usize = 36;
+
+/// The size of the markup of a folder without attributes and children, its indentation included.
+#[cfg(feature = "render")]
+const FOLDER_SIZE: usize = 35;
+
+#[cfg(feature = "render")]
+fn attribute_size(name: &str, value: &str) -> usize {
+    if value.is_empty() {
+        0
+    } else {
+        name.len() + value.len() + 4
+    }
+}
+
+#[cfg(feature = "render")]
+fn estimate_children(children: &[Item]) -> usize {
+    let mut size = 0;
+
+    for item in children {
+        size += match item {
+            Item::Subfolder(f) => {
+                let extra: usize = f
+                    .extra_attributes
+                    .iter()
+                    .map(|(name, value)| name.len() + value.len() + 4)
+                    .sum();
+
+                FOLDER_SIZE
+                    + f.title.len()
+                    + if f.folded { " FOLDED".len() } else { 0 }
+                    + attribute_size("ADD_DATE", &f.add_date)
+                    + attribute_size("LAST_MODIFIED", &f.last_modified)
+                    + if f.personal_toolbar_folder {
+                        " PERSONAL_TOOLBAR_FOLDER=\"true\"".len()
+                    } else {
+                        0
+                    }
+                    + if f.unfiled_bookmarks_folder {
+                        " UNFILED_BOOKMARKS_FOLDER=\"true\"".len()
+                    } else {
+                        0
+                    }
+                    + extra
+                    + estimate_children(&f.children)
+            }
+            Item::Shortcut(b) => {
+                BOOKMARK_SIZE
+                    + b.href.len()
+                    + b.add_date.len()
+                    + b.title.len()
+                    + attribute_size("LAST_VISIT", &b.last_visit)
+                    + attribute_size("LAST_MODIFIED", &b.last_modified)
+                    + attribute_size("ICON_URI", &b.icon_uri)
+                    + attribute_size("ICON", &b.icon)
+                    + attribute_size("TARGET", &b.target)
+                    + attribute_size("REL", &b.rel)
+                    + attribute_size("SHORTCUTURL", &b.shortcut_url)
+                    + if b.description.is_empty() {
+                        0
+                    } else {
+                        "\n<DD>".len() + b.description.len()
+                    }
+            }
+        };
+    }
+
+    size
+}
+
+/// Derives the empty dates of the nested [Folder]s, and returns the `(oldest added, newest)` dates of the items.
+fn derive_folder_dates(children: &mut [Item]) -> (Option<i64>, Option<i64>) {
+    let mut oldest: Option<i64> = None;
+    let mut newest: Option<i64> = None;
+
+    let mut observe = |added: &str, modified: &str| {
+        if let Some(added) = parse_date(added).map(|d| d.timestamp()) {
+            oldest = Some(oldest.map_or(added, |o| o.min(added)));
+            newest = Some(newest.map_or(added, |n| n.max(added)));
+        }
+
+        if let Some(modified) = parse_date(modified).map(|d| d.timestamp()) {
+            newest = Some(newest.map_or(modified, |n| n.max(modified)));
+        }
+    };
+
+    for item in children.iter_mut() {
+        match item {
+            Item::Subfolder(f) => {
+                let (added, modified) = derive_folder_dates(&mut f.children);
+
+                if f.add_date.trim().is_empty() {
+                    if let Some(added) = added {
+                        f.add_date = added.to_string();
+                    }
+                }
+
+                if f.last_modified.trim().is_empty() {
+                    if let Some(modified) = modified {
+                        f.last_modified = modified.to_string();
+                    }
+                }
+
+                observe(&f.add_date, &f.last_modified);
+            }
+            Item::Shortcut(b) => observe(&b.add_date, &b.last_modified),
+        }
+    }
+
+    (oldest, newest)
+}
+
+fn truncate_titles(children: &mut [Item], max_len: usize) {
+    for child in children {
+        let title = match child {
+            Item::Subfolder(f) => {
+                truncate_titles(&mut f.children, max_len);
+                &mut f.title
+            }
+            Item::Shortcut(b) => &mut b.title,
+        };
+
+        if let Some((index, _)) = title.char_indices().nth(max_len) {
+            title.truncate(index);
+            title.push('…');
+        }
+    }
+}
+
+/// Writes an escaped item without its children, then its `children` at the place of the closing `</DL>`.
+/// The children are escaped one by one, with the [EscapePolicy] of the `options`.
+#[cfg(feature = "render")]
+fn write_shell<W: Write, T: Template>(
+    writer: &mut W,
+    shell: &T,
+    children: &[Item],
+    options: &RenderOptions,
+) -> Result<(), Error> {
+    let rendered = shell.render().map_err(Error::other)?;
+    let (opening, closing) = rendered.split_at(rendered.rfind("\n</DL>").unwrap_or(rendered.len()));
+
+    writer.write_all(options.apply(opening).as_bytes())?;
+
+    for child in children {
+        writer.write_all(options.apply("\n    ").as_bytes())?;
+
+        match escape_shell(child, options.escape_policy) {
+            Item::Subfolder(shell) => {
+                write_shell(writer, &shell, child.children(), options)?;
+                writer.flush()?;
+            }
+            Item::Shortcut(b) => {
+                let rendered = b.render().map_err(Error::other)?;
+                writer.write_all(options.apply(&rendered).as_bytes())?;
+            }
+        }
+    }
+
+    writer.write_all(options.apply(closing).as_bytes())
+}
+
+/// The prefix of the comment that stores the `metadata` of a document.
+const METADATA_PREFIX: &str = "bookmarkt-meta:";
+
+/// The UTF-8 byte order mark, prepended by [Netscape::to_bytes] on demand.
+#[cfg(feature = "render")]
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+fn select_metadata(node: &NodeRef) -> HashMap<String, String> {
+    node.inclusive_descendants()
+        .find_map(|n| {
+            n.as_comment().and_then(|comment| {
+                comment
+                    .borrow()
+                    .trim()
+                    .strip_prefix(METADATA_PREFIX)
+                    .map(String::from)
+            })
+        })
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Gets the charset declared by a `<META CHARSET/>` or a `<META HTTP-EQUIV="Content-Type"/>` tag.
+fn select_charset(meta: &NodeRef) -> Option<String> {
+    if let Some(attribute) = meta.select_attribute("CHARSET") {
+        return Some(attribute.value.trim().to_string());
+    }
+
+    meta.select_attribute("CONTENT").and_then(|attribute| {
+        let lower = attribute.value.to_ascii_lowercase();
+
+        lower.find("charset=").map(|index| {
+            let declared = &attribute.value[index + "charset=".len()..];
+            declared.trim().trim_matches('"').to_string()
+        })
+    })
+}
+
+/// Replaces the `charset` declared by the `raw` text with `UTF-8`, the encoding of the rendered documents.
+#[cfg(feature = "render")]
+fn declare_utf8<'a>(raw: &'a str, charset: &str) -> std::borrow::Cow<'a, str> {
+    if charset.is_empty() || charset.eq_ignore_ascii_case("UTF-8") {
+        return raw.into();
+    }
+
+    let declared = raw
+        .to_ascii_lowercase()
+        .find("charset=")
+        .map(|index| index + "charset=".len())
+        .map(|start| start + raw[start..].len() - raw[start..].trim_start_matches(['"', ' ']).len())
+        .filter(|start| {
+            raw.get(*start..start + charset.len())
+                .is_some_and(|value| value.eq_ignore_ascii_case(charset))
+        });
+
+    match declared {
+        Some(start) => format!("{}UTF-8{}", &raw[..start], &raw[start + charset.len()..]).into(),
+        None => raw.into(),
+    }
+}
+
+fn collect_duplicate_folders(
+    children: &[Item],
+    path: &mut Vec<String>,
+    paths: &mut Vec<Vec<String>>,
+) {
+    let mut titles = HashSet::new();
+    let mut duplicates = HashSet::new();
+
+    for item in children {
+        if let Item::Subfolder(f) = item {
+            if !titles.insert(&f.title) && duplicates.insert(&f.title) {
+                let mut duplicate = path.clone();
+                duplicate.push(f.title.clone());
+                paths.push(duplicate);
+            }
+
+            path.push(f.title.clone());
+            collect_duplicate_folders(&f.children, path, paths);
+            path.pop();
+        }
+    }
+}
+
+/// The conventional titles of the toolbar folders, when no folder is flagged as the toolbar.
+const TOOLBAR_TITLES: [&str; 3] = ["Bookmarks Toolbar", "Bookmarks bar", "Favorites bar"];
+
+/// Stores the metadata written next to an export, see [Netscape::export_with_sidecar].
+#[cfg(feature = "render")]
+#[derive(Serialize)]
+struct ExportMetadata {
+    export_date: String,
+    #[serde(flatten)]
+    stats: Stats,
+}
+
+fn reverse_children(children: &mut [Item]) {
+    children.reverse();
+
+    for item in children.iter_mut() {
+        if let Item::Subfolder(f) = item {
+            reverse_children(&mut f.children);
+        }
+    }
+}
+
+fn collect_table_of_contents(children: &[Item], depth: usize, contents: &mut Vec<(usize, String)>) {
+    for item in children {
+        if let Item::Subfolder(f) = item {
+            contents.push((depth, f.title.clone()));
+            collect_table_of_contents(&f.children, depth + 1, contents);
+        }
+    }
+}
+
+/// Counts the nested [Bookmark]s of every nested [Folder], in the document order.
+fn collect_folder_counts(
+    children: &[Item],
+    path: &mut Vec<String>,
+    counts: &mut Vec<(Vec<String>, usize)>,
+) {
+    for item in children {
+        if let Item::Subfolder(f) = item {
+            path.push(f.title.clone());
+            counts.push((path.clone(), f.children.shortcuts().len()));
+            collect_folder_counts(&f.children, path, counts);
+            path.pop();
+        }
+    }
+}
+
+impl NetscapeBuilder {
+    /// Appends a [Folder] to the root of the document.
+    pub fn add_folder(&mut self, folder: Folder) -> &mut Self {
+        self.children
+            .get_or_insert_with(Vec::new)
+            .push(Item::Subfolder(folder));
+        self
+    }
+
+    /// Appends a [Bookmark] to the root of the document.
+    pub fn add_bookmark(&mut self, bookmark: Bookmark) -> &mut Self {
+        self.children
+            .get_or_insert_with(Vec::new)
+            .push(Item::Shortcut(bookmark));
+        self
+    }
+
+    /// The `h1` falls back on the `title` when it isn't set.
+    fn default_h1(&self) -> String {
+        self.title.clone().unwrap_or_default()
+    }
+}
+
+impl PartialEq for Netscape {
+    fn eq(&self, other: &Self) -> bool {
+        self.title == other.title
+            && self.h1 == other.h1
+            && self.add_date == other.add_date
+            && self.last_modified == other.last_modified
+            && self.metadata == other.metadata
+            && self.children == other.children
+    }
+}
+
+#[allow(dead_code)]
+fn sanitize_file(path: &Path) -> String {
+    use std::fs::File;
+    use std::io::{BufRead, BufReader};
+
+    let file = File::open(path).unwrap();
+    let mut contents = String::new();
+
+    for content in BufReader::new(file).lines().map_while(Result::ok) {
+        contents.push_str(content.trim());
+    }
+
+    contents
+}
+
+#[allow(dead_code)]
+fn sanitize_string(string: String) -> String {
+    let mut contents = String::new();
+
+    for line in string.lines() {
+        contents.push_str(line.trim());
+    }
+
+    contents
+}
+
+#[test]
+fn should_parse_netscape_header() {
+    let html = r"
+<!DOCTYPE NETSCAPE-Bookmark-file-1>
+    <!--This is an automatically generated file.
+    It will be read and overwritten.
+    Do Not Edit! -->
+    <Title>Collection Title</Title>
+    <H1>Collection Head</H1>";
+    let netscape = Netscape::from_html(html).unwrap();
+
+    assert_eq!(netscape.title, "Collection Title");
+    assert_eq!(netscape.h1, "Collection Head");
+}
+
+#[test]
+fn should_parse_netscape_file() {
+    use crate::bookmark::BookmarkBuilder;
+
+    let path = Path::new("./res/netscape.html");
+    let label = String::from("Bookmarks");
+
+    let b1 = BookmarkBuilder::default()
+        .href("https://framasoft.org/")
+        .add_date("1466009059")
+        .title("Framasoft ~ Page portail du réseau")
+        .build()
+        .unwrap();
+
+    let b2 = BookmarkBuilder::default()
+        .href("https://www.kernel.org/")
+        .add_date("1466009167")
+        .title("The Linux Kernel Archives")
+        .build()
+        .unwrap();
+
+    let netscape = Netscape {
+        title: label.clone(),
+        h1: label,
+        children: vec![Item::Shortcut(b1), Item::Shortcut(b2)],
+        ..Netscape::default()
+    };
+
+    assert_eq!(Netscape::from_file(path).unwrap(), netscape);
+}
+
+#[test]
+fn should_serialize_json_netscape() {
+    let b1 = r#"{"href":"https://framasoft.org/","title":"Framasoft ~ Page portail du réseau","add_date":"1466009059","last_visit":"","last_modified":"","icon_uri":"","icon":"","target":"","rel":"","shortcut_url":"","description":""}"#;
+    let b2 = r#"{"href":"https://www.kernel.org/","title":"The Linux Kernel Archives","add_date":"1466009167","last_visit":"","last_modified":"","icon_uri":"","icon":"","target":"","rel":"","shortcut_url":"","description":""}"#;
+
+    let json = format!(
+        r#"{{"title":"Bookmarks","h1":"Bookmarks","add_date":"","last_modified":"","charset":"UTF-8","metadata":{{}},"children":[{},{}]}}"#,
+        b1, b2
+    );
+
+    let path = Path::new("./res/netscape.html");
+    let netscape = Netscape::from_file(path).unwrap();
+
+    assert_eq!(netscape.to_json().unwrap(), json)
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_render_netscape_html() {
+    use crate::bookmark::BookmarkBuilder;
+    use std::fs;
+
+    let label = String::from("Bookmarks");
+    let path = Path::new("./res/netscape.html");
+
+    let b1 = BookmarkBuilder::default()
+        .href("https://framasoft.org/")
+        .add_date("1466009059")
+        .title("Framasoft ~ Page portail du réseau")
+        .build()
+        .unwrap();
+
+    let b2 = BookmarkBuilder::default()
+        .href("https://www.kernel.org/")
+        .add_date("1466009167")
+        .title("The Linux Kernel Archives")
+        .build()
+        .unwrap();
+
+    let netscape = Netscape {
+        title: label.clone(),
+        h1: label,
+        children: vec![Item::Shortcut(b1), Item::Shortcut(b2)],
+        ..Netscape::default()
+    };
+
+    assert_eq!(
+        netscape.to_html().unwrap(),
+        fs::read_to_string(path).unwrap().trim()
+    )
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_roundtrip_chromium_html() {
+    let path = Path::new("./res/chromium.html");
+    let chromium = Netscape::from_file(path).unwrap();
+
+    let imported = sanitize_file(path);
+    let mut parsed = sanitize_string(chromium.to_html().unwrap());
+
+    // the chromium import add a last <p> tag
+    parsed.push_str("<p>");
+
+    assert_eq!(imported, parsed)
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_roundtrip_firefox_html() {
+    let path = Path::new("./res/firefox.html");
+    let firefox = Netscape::from_file(path).unwrap();
+
+    let imported = sanitize_file(path);
+    let parsed = sanitize_string(firefox.to_html().unwrap());
+
+    assert_eq!(parsed, imported)
+}
+
+#[test]
+fn should_count_top_level_bookmarks() {
+    let path = Path::new("./res/netscape.html");
+    let netscape = Netscape::from_file(path).unwrap();
+
+    assert_eq!(netscape.top_level_counts(), vec![(String::new(), 2)]);
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_roundtrip_netscape_links() {
+    let links = vec![
+        (
+            String::from("Framasoft"),
+            String::from("https://framasoft.org/"),
+        ),
+        (
+            String::from("The Linux Kernel Archives"),
+            String::from("https://www.kernel.org/"),
+        ),
+    ];
+    let netscape = Netscape::from_links("Links", links);
+    let html = netscape.to_html().unwrap();
+
+    assert_eq!(netscape.get_bookmarks().len(), 2);
+    assert_eq!(Netscape::from_html(&html).unwrap(), netscape);
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_roundtrip_netscape_dates() {
+    let html = r#"
+<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<TITLE>Bookmarks</TITLE>
+<H1 ADD_DATE="1591573917" LAST_MODIFIED="1600910622">Bookmarks</H1>
+<DL><p>
+</DL>"#;
+    let netscape = Netscape::from_html(html).unwrap();
+    let reimported = Netscape::from_html(&netscape.to_html().unwrap()).unwrap();
+
+    assert_eq!(netscape.add_date, "1591573917");
+    assert_eq!(netscape.last_modified, "1600910622");
+    assert_eq!(reimported.add_date, netscape.add_date);
+    assert_eq!(reimported.last_modified, netscape.last_modified);
+    assert_eq!(reimported, netscape);
+
+    let mut modified = Netscape::from_html(html).unwrap();
+    modified.last_modified = String::from("1600910700");
+    assert_ne!(modified, netscape);
+}
+
+#[test]
+fn should_build_netscape_incrementally() {
+    use crate::bookmark::BookmarkBuilder;
+    use crate::folder::FolderBuilder;
+
+    let bookmark = BookmarkBuilder::default()
+        .href("https://www.kernel.org/")
+        .title("The Linux Kernel Archives")
+        .build()
+        .unwrap();
+    let folder = FolderBuilder::default().title("folder").build().unwrap();
+
+    let netscape = Netscape::builder()
+        .title("Bookmarks")
+        .h1("Root")
+        .add_folder(folder.clone())
+        .add_bookmark(bookmark.clone())
+        .build()
+        .unwrap();
+
+    assert_eq!(netscape.title, "Bookmarks");
+    assert_eq!(netscape.h1, "Root");
+    assert_eq!(
+        netscape.children,
+        vec![Item::Subfolder(folder), Item::Shortcut(bookmark)]
+    );
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_roundtrip_distinct_title_and_h1() {
+    let mut netscape = Netscape::new("Bookmarks", "Bookmarks Menu");
+    netscape.children.push(Item::Shortcut(Bookmark::from((
+        "Kernel",
+        "https://www.kernel.org/",
+    ))));
+
+    let rendered = netscape.to_html().unwrap();
+    let parsed = Netscape::from_html(&rendered).unwrap();
+
+    assert!(rendered.contains("<TITLE>Bookmarks</TITLE>"));
+    assert!(rendered.contains(">Bookmarks Menu</H1>"));
+    assert_eq!(parsed.title, "Bookmarks");
+    assert_eq!(parsed.h1, "Bookmarks Menu");
+    assert_eq!(parsed, netscape);
+}
+
+#[test]
+fn should_find_duplicate_folder_paths() {
+    use crate::folder::FolderBuilder;
+
+    let work = FolderBuilder::default().title("Work").build().unwrap();
+    let nested = FolderBuilder::default()
+        .title("Nested")
+        .children(vec![
+            Item::Subfolder(work.clone()),
+            Item::Subfolder(work.clone()),
+        ])
+        .build()
+        .unwrap();
+
+    let netscape = Netscape::builder()
+        .add_folder(work.clone())
+        .add_folder(work.clone())
+        .add_folder(work)
+        .add_folder(nested)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        netscape.duplicate_folder_paths(),
+        vec![
+            vec![String::from("Work")],
+            vec![String::from("Nested"), String::from("Work")],
+        ]
+    );
+}
+
+#[test]
+fn should_select_bookmarks_added_between() {
+    use chrono::TimeZone;
+
+    let path = Path::new("./res/chromium.html");
+    let chromium = Netscape::from_file(path).unwrap();
+
+    let start = Utc.timestamp_opt(1600910526, 0).unwrap();
+    let end = Utc.timestamp_opt(1600910622, 0).unwrap();
+    let added = chromium.added_between(start, end);
+
+    assert_eq!(added.len(), 3);
+    assert_eq!(added[0].add_date, "1600910526");
+    assert_eq!(added[2].add_date, "1600910622");
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_render_netscape_charset_as_utf8() {
+    let html = r#"
+<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=windows-1252">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>"#;
+    let netscape = Netscape::from_html(html).unwrap();
+    let rendered = netscape.to_html().unwrap();
+
+    assert_eq!(netscape.charset, "windows-1252");
+    assert!(rendered.contains(r#"CONTENT="text/html; charset=UTF-8""#));
+    assert_eq!(Netscape::from_html(&rendered).unwrap().charset, "UTF-8");
+
+    let options = ParseOptions {
+        preserve_whitespace: true,
+        ..ParseOptions::default()
+    };
+    let preserved = Netscape::from_html_with(html, &options).unwrap();
+
+    assert_eq!(
+        preserved.to_html().unwrap(),
+        html.replace("charset=windows-1252", "charset=UTF-8")
+    );
+
+    let path = std::env::temp_dir().join("bookmarkt_windows_1252.html");
+    std::fs::write(&path, b"<DL><p><DT><A HREF=\"url\">caf\xE9</A></DL><p>").unwrap();
+    let lossy = Netscape::from_file_with(&path, &options).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(lossy.get_bookmarks()[0].title, "caf\u{FFFD}");
+    assert!(Netscape::default()
+        .to_html()
+        .unwrap()
+        .contains(r#"CONTENT="text/html; charset=UTF-8""#));
+}
+
+#[test]
+fn should_render_html_table() {
+    use crate::bookmark::BookmarkBuilder;
+    use crate::folder::FolderBuilder;
+
+    let bookmark = BookmarkBuilder::default()
+        .href(r#"https://example.com/?a=1&b="2""#)
+        .title("<Tom & Jerry>")
+        .add_date("1466009059")
+        .build()
+        .unwrap();
+    let folder = FolderBuilder::default()
+        .title("Cartoons")
+        .children(vec![Item::Shortcut(bookmark)])
+        .build()
+        .unwrap();
+    let netscape = Netscape::builder().add_folder(folder).build().unwrap();
+
+    assert_eq!(
+        netscape.to_html_table(),
+        r#"<table>
+<thead><tr><th>Title</th><th>Folder</th><th>Added</th></tr></thead>
+<tbody>
+<tr><td><a href="https://example.com/?a=1&amp;b=&quot;2&quot;">&lt;Tom &amp; Jerry&gt;</a></td><td>Cartoons</td><td>2016-06-15 16:44:19</td></tr>
+</tbody>
+</table>
+"#
+    );
+}
+
+#[test]
+fn should_parse_chromium_nested_folders() {
+    let path = Path::new("./res/chromium_nested.html");
+    let chromium = Netscape::from_file(path).unwrap();
+
+    let bar = chromium.children[0].take_subfolder().unwrap();
+    let rust = bar.children[0].take_subfolder().unwrap();
+    let crates = rust.children[0].take_subfolder().unwrap();
+    let empty = bar.children[1].take_subfolder().unwrap();
+
+    assert_eq!(chromium.children.len(), 2);
+    assert_eq!(bar.children.len(), 3);
+    assert_eq!(rust.title, "Rust");
+    assert_eq!(rust.children.len(), 2);
+    assert_eq!(crates.children.len(), 1);
+    assert!(empty.children.is_empty());
+    assert!(bar.children[2].is_shortcut());
+    assert_eq!(chromium.get_folders().len(), 4);
+    assert_eq!(chromium.get_bookmarks().len(), 4);
+}
+
+#[test]
+fn should_extract_bookmarks_in_document_order() {
+    use std::fs;
+
+    let path = Path::new("./res/firefox.html");
+    let raw = fs::read_to_string(path).unwrap();
+    let firefox = Netscape::from_file(path).unwrap();
+
+    let extracted = Netscape::extract_bookmarks(&raw);
+    let expected: Vec<Bookmark> = firefox.get_bookmarks().into_iter().cloned().collect();
+
+    assert_eq!(extracted, expected);
+}
+
+#[test]
+fn should_sort_bookmarks_by_date() {
+    let links = vec![
+        (String::from("undated"), String::from("url")),
+        (String::from("new"), String::from("url")),
+        (String::from("old"), String::from("url")),
+        (String::from("also new"), String::from("url")),
+    ];
+    let mut netscape = Netscape::from_links("Links", links);
+
+    for bookmark in netscape.bookmarks_mut() {
+        bookmark.add_date = match bookmark.title.as_str() {
+            "old" => String::from("1466009059"),
+            "undated" => String::from("date"),
+            _ => String::from("1466009167"),
+        };
+    }
+
+    netscape.sort_by_date();
+    let titles: Vec<&str> = netscape
+        .get_bookmarks()
+        .iter()
+        .map(|b| b.title.as_str())
+        .collect();
+
+    assert_eq!(titles, vec!["old", "new", "also new", "undated"]);
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_report_lossy_roundtrip() {
+    let links = vec![(String::from("a\rb"), String::from("url"))];
+    let netscape = Netscape::from_links("Links", links);
+
+    assert_eq!(
+        netscape.roundtrip_check(),
+        Err(String::from(r#"/0: `title` differs ("a\rb" != "a\nb")"#))
+    );
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_estimate_rendered_size() {
+    for file in &[
+        "./res/netscape.html",
+        "./res/chromium.html",
+        "./res/firefox.html",
+        "./res/descriptions.html",
+    ] {
+        let netscape = Netscape::from_file(Path::new(file)).unwrap();
+
+        assert_eq!(netscape.estimated_size(), netscape.to_html().unwrap().len());
+    }
+
+    assert_eq!(
+        Netscape::default().estimated_size(),
+        Netscape::default().to_html().unwrap().len()
+    );
+}
+
+#[test]
+fn should_merge_bookmarks_by_date() {
+    use crate::bookmark::BookmarkBuilder;
+    use crate::item::folder_item as folder;
+
+    let bookmark = |title: &str, add_date: &str| {
+        Item::Shortcut(
+            BookmarkBuilder::default()
+                .href("url")
+                .title(title)
+                .add_date(add_date)
+                .build()
+                .unwrap(),
+        )
+    };
+
+    let mut mine = Netscape::builder()
+        .children(vec![folder(
+            "shared",
+            vec![bookmark("a", "1"), bookmark("c", "3"), bookmark("e", "")],
+        )])
+        .build()
+        .unwrap();
+
+    let theirs = Netscape::builder()
+        .children(vec![
+            folder(
+                "shared",
+                vec![bookmark("f", ""), bookmark("d", "3"), bookmark("b", "2")],
+            ),
+            folder("theirs", vec![bookmark("g", "1")]),
+        ])
+        .build()
+        .unwrap();
+
+    mine.merge_by_date(theirs);
+    let titles: Vec<&str> = mine
+        .get_bookmarks()
+        .iter()
+        .map(|b| b.title.as_str())
+        .collect();
+
+    assert_eq!(mine.children.len(), 2);
+    assert_eq!(titles, vec!["a", "b", "c", "d", "e", "f", "g"]);
+}
+
+#[test]
+fn should_merge_sibling_folders() {
+    use crate::item::{bookmark_item, folder_item as folder};
+
+    let bookmark = |title: &str| bookmark_item(title, "url");
+
+    let mut netscape = Netscape::builder()
+        .children(vec![
+            folder(
+                "Work",
+                vec![bookmark("a"), folder("Meetings", vec![bookmark("b")])],
+            ),
+            bookmark("c"),
+            folder(
+                "Work",
+                vec![folder("Meetings", vec![bookmark("d")]), bookmark("e")],
+            ),
+        ])
+        .build()
+        .unwrap();
+
+    netscape.merge_sibling_folders();
+
+    let expected = vec![
+        folder(
+            "Work",
+            vec![
+                bookmark("a"),
+                folder("Meetings", vec![bookmark("b"), bookmark("d")]),
+                bookmark("e"),
+            ],
+        ),
+        bookmark("c"),
+    ];
+
+    assert_eq!(netscape.children, expected);
+}
+
+#[test]
+fn should_truncate_multibyte_titles() {
+    let links = vec![
+        (String::from("朽木朽木朽木"), String::from("url")),
+        (String::from("朽木"), String::from("url")),
+    ];
+    let mut netscape = Netscape::from_links("Links", links);
+    netscape.truncate_titles(2);
+
+    let titles: Vec<&str> = netscape
+        .get_bookmarks()
+        .iter()
+        .map(|b| b.title.as_str())
+        .collect();
+
+    assert_eq!(titles, vec!["朽木…", "朽木"]);
+}
+
+#[test]
+fn should_parse_dates_set_on_dt() {
+    let netscape = Netscape::from_file(Path::new("./res/dt_dates.html")).unwrap();
+    let folder = &netscape.get_folders()[0];
+    let bookmarks = netscape.get_bookmarks();
+
+    assert_eq!(folder.add_date, "1600910474");
+    assert_eq!(folder.last_modified, "1600910622");
+    assert_eq!(bookmarks[0].add_date, "1600910526");
+    assert_eq!(bookmarks[0].last_visit, "1600910527");
+    assert_eq!(bookmarks[1].add_date, "1600910600");
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_write_nested_folders_like_to_html() {
+    let nested = Netscape::from_file(Path::new("./res/chromium_nested.html")).unwrap();
+    let mut output = vec![];
+    nested.write_to(&mut output).unwrap();
 
     assert_eq!(
-        netscape.to_html().unwrap(),
-        fs::read_to_string(path).unwrap().trim()
-    )
+        String::from_utf8(output).unwrap(),
+        nested.to_html().unwrap()
+    );
 }
 
 #[test]
-fn should_roundtrip_chromium_html() {
+fn should_fill_missing_titles_with_default() {
+    let options = ParseOptions {
+        default_title: Some(String::from("export.html")),
+        ..ParseOptions::default()
+    };
+    let untitled = r#"<DL><p><DT><A HREF="url">name</A></DL>"#;
+    let titled = r#"<TITLE>Bookmarks</TITLE><DL><p></DL>"#;
+
+    let filled = Netscape::from_html_with(untitled, &options).unwrap();
+    let kept = Netscape::from_html_with(titled, &options).unwrap();
+
+    assert_eq!(
+        (filled.title.as_str(), filled.h1.as_str()),
+        ("export.html", "export.html")
+    );
+    assert_eq!((kept.title.as_str(), kept.h1.as_str()), ("Bookmarks", ""));
+    assert_eq!(Netscape::from_html(untitled).unwrap().title, "");
+}
+
+#[test]
+fn should_fill_only_missing_or_malformed_dates() {
+    use chrono::TimeZone;
+
+    let mut netscape = Netscape::from_links(
+        "Links",
+        vec![
+            (String::from("empty"), String::from("url")),
+            (String::from("malformed"), String::from("url")),
+            (String::from("valid"), String::from("url")),
+        ],
+    );
+    let dates = ["", "yesterday", "1466009059"];
+
+    for (bookmark, date) in netscape.bookmarks_mut().zip(dates.iter()) {
+        bookmark.add_date = String::from(*date);
+    }
+
+    netscape.fill_missing_dates(Utc.timestamp_opt(1600910474, 0).unwrap());
+
+    let dates: Vec<&str> = netscape
+        .get_bookmarks()
+        .iter()
+        .map(|b| b.add_date.as_str())
+        .collect();
+
+    assert_eq!(dates, vec!["1600910474", "1600910474", "1466009059"]);
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_roundtrip_descriptions() {
+    let netscape = Netscape::from_file(Path::new("./res/descriptions.html")).unwrap();
+    let html = netscape.to_html().unwrap();
+
+    assert!(html.contains("crates.io</A>\n<DD>After the DT\n"));
+    assert_eq!(netscape.roundtrip_check(), Ok(()));
+}
+
+#[test]
+fn should_group_bookmarks_by_year() {
+    let mut netscape = Netscape::from_links(
+        "Links",
+        vec![
+            (String::from("2020"), String::from("a")),
+            (String::from("undated"), String::from("b")),
+            (String::from("2016"), String::from("c")),
+        ],
+    );
+    let dates = ["1600910474", "", "1466009059"];
+
+    for (bookmark, date) in netscape.bookmarks_mut().zip(dates.iter()) {
+        bookmark.add_date = String::from(*date);
+    }
+
+    assert_eq!(
+        netscape.group_by_year().top_level_counts(),
+        vec![
+            (String::from("2016"), 1),
+            (String::from("2020"), 1),
+            (String::from("Undated"), 1),
+        ]
+    );
+}
+
+#[test]
+fn should_list_toolbar_title_conflicts() {
+    let folder = |title: &str| {
+        Item::Subfolder(Folder {
+            title: String::from(title),
+            ..Folder::default()
+        })
+    };
+
+    let netscape = Netscape {
+        children: vec![Item::Subfolder(Folder {
+            title: String::from("Toolbar"),
+            personal_toolbar_folder: true,
+            children: vec![
+                folder("Work"),
+                folder("News"),
+                folder("Work"),
+                folder("Work"),
+            ],
+            ..Folder::default()
+        })],
+        ..Netscape::default()
+    };
+
+    assert_eq!(
+        netscape.toolbar_title_conflicts(),
+        vec![String::from("Work")]
+    );
+    assert!(Netscape::default().toolbar_title_conflicts().is_empty());
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_report_parsing_progress_periodically() {
+    let links: Vec<(String, String)> = (0..2500)
+        .map(|i| (i.to_string(), String::from("url")))
+        .collect();
+    let raw = Netscape::from_links("Links", links).to_html().unwrap();
+    let mut reported = vec![];
+
+    let netscape = Netscape::from_html_with_progress(&raw, |count| reported.push(count)).unwrap();
+
+    assert_eq!(netscape.get_bookmarks().len(), 2500);
+    assert_eq!(reported, vec![1000, 2000, 2500]);
+}
+
+#[test]
+fn should_locate_duplicated_bookmark_by_reference() {
+    let folder = |title: &str| {
+        Item::Subfolder(Folder {
+            title: String::from(title),
+            children: vec![Item::Shortcut(Bookmark::from(("same", "url")))],
+            ..Folder::default()
+        })
+    };
+    let netscape = Netscape {
+        children: vec![folder("first"), folder("second")],
+        ..Netscape::default()
+    };
+    let second = netscape.get_bookmarks()[1];
+
+    assert_eq!(netscape.path_of(second), Some(vec![String::from("second")]));
+    assert_eq!(
+        netscape.path_of(&second.clone()),
+        Some(vec![String::from("first")])
+    );
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_roundtrip_metadata_comment() {
+    let mut netscape = Netscape::from_file(Path::new("./res/netscape.html")).unwrap();
+    netscape
+        .metadata
+        .insert(String::from("device"), String::from("laptop --> desktop"));
+    netscape
+        .metadata
+        .insert(String::from("cursor"), String::from("42"));
+
+    let html = netscape.to_html().unwrap();
+
+    assert!(html.contains(
+        "-->\n<!-- bookmarkt-meta: {\"cursor\":\"42\",\"device\":\"laptop -\\u002d> desktop\"} -->\n<META"
+    ));
+    assert_eq!(Netscape::from_html(&html).unwrap(), netscape);
+    assert_eq!(netscape.estimated_size(), html.len());
+    assert!(Netscape::from_file(Path::new("./res/netscape.html"))
+        .unwrap()
+        .metadata
+        .is_empty());
+}
+
+#[test]
+fn should_remove_domains_and_subdomains_only() {
+    let mut netscape = Netscape::from_links(
+        "Links",
+        vec![
+            (String::from("a"), String::from("https://example.com/")),
+            (
+                String::from("b"),
+                String::from("https://www.EXAMPLE.com/page"),
+            ),
+            (String::from("c"), String::from("https://notexample.com/")),
+            (String::from("d"), String::from("example.com")),
+        ],
+    );
+
+    assert_eq!(netscape.remove_domains(&["example.com"]), 2);
+
+    let titles: Vec<&str> = netscape
+        .get_bookmarks()
+        .iter()
+        .map(|b| b.title.as_str())
+        .collect();
+
+    assert_eq!(titles, vec!["c", "d"]);
+}
+
+#[test]
+fn should_keep_and_complete_the_richest_duplicate() {
+    let mut netscape = Netscape::from_links(
+        "Links",
+        vec![
+            (String::from(""), String::from("url")),
+            (String::from("other"), String::from("other")),
+            (String::from("rich"), String::from("url")),
+            (String::from("poor"), String::from("url")),
+        ],
+    );
+    let mut bookmarks = netscape.bookmarks_mut();
+    bookmarks.next().unwrap().icon = String::from("icon");
+    bookmarks.next();
+    let rich = bookmarks.next().unwrap();
+    rich.add_date = String::from("1466009059");
+    rich.description = String::from("description");
+    drop(bookmarks);
+
+    assert_eq!(netscape.dedup_richest(), 2);
+
+    let kept = netscape.get_bookmarks();
+    assert_eq!(kept.len(), 2);
+    assert_eq!(kept[0].title, "other");
+    assert_eq!(
+        (
+            kept[1].title.as_str(),
+            kept[1].icon.as_str(),
+            kept[1].description.as_str()
+        ),
+        ("rich", "icon", "description")
+    );
+}
+
+#[test]
+fn should_parse_netscape_from_subtree() {
+    let page = r#"<!DOCTYPE html>
+<html><head><title>Blog</title></head>
+<body>
+<nav><A HREF="https://example.com/home">Home</A></nav>
+<div id="bookmarks">
+<H1 ADD_DATE="1600910474">Links</H1>
+<DL><p>
+    <DT><H3>Rust</H3>
+    <DL><p>
+        <DT><A HREF="https://www.rust-lang.org/">Rust</A>
+    </DL><p>
+    <DT><A HREF="https://www.kernel.org/">Kernel</A>
+</DL><p>
+</div>
+</body></html>"#;
+
+    use kuchiki::parse_html;
+    use kuchiki::traits::TendrilSink;
+
+    let document = parse_html().one(page);
+    let div = document.select_first("#bookmarks").unwrap();
+    let netscape = Netscape::from_node(div.as_node()).unwrap();
+
+    assert_eq!(netscape.title, "");
+    assert_eq!(netscape.h1, "Links");
+    assert_eq!(netscape.add_date, "1600910474");
+    assert_eq!(netscape.children.len(), 2);
+    assert!(netscape.children[0].is_subfolder());
+    assert_eq!(
+        netscape.children[1].take_shortcut().unwrap().href,
+        "https://www.kernel.org/"
+    );
+
+    let html = document.select_first("html").unwrap();
+    assert_eq!(Netscape::from_node(html.as_node()).unwrap().title, "Blog");
+}
+
+#[test]
+fn should_render_interactive_html_escaped() {
+    let netscape = Netscape {
+        title: String::from("Tom & Jerry"),
+        h1: String::from("Tom & Jerry"),
+        children: vec![Item::Subfolder(Folder {
+            title: String::from("<script>"),
+            folded: true,
+            children: vec![Item::Shortcut(Bookmark::from((
+                "a \"quoted\" title",
+                "https://example.com/?a=1&b=2",
+            )))],
+            ..Folder::default()
+        })],
+        ..Netscape::default()
+    };
+
+    let page = netscape.to_interactive_html();
+
+    assert!(page.contains("<title>Tom &amp; Jerry</title>"));
+    assert!(page.contains("<details><summary>&lt;script&gt;</summary>"));
+    assert!(page.contains(
+        r#"<li><a href="https://example.com/?a=1&amp;b=2">a &quot;quoted&quot; title</a></li>"#
+    ));
+    assert!(page.ends_with("</html>\n"));
+}
+
+#[test]
+fn should_backfill_titles_from_urls() {
+    let links = vec![
+        (String::from(" "), String::from("https://www.kernel.org/")),
+        (String::from(""), String::from("javascript:alert(1)")),
+        (String::from(""), String::from("not a url")),
+        (
+            String::from("Rust"),
+            String::from("https://www.rust-lang.org/"),
+        ),
+    ];
+    let mut netscape = Netscape::from_links("Links", links);
+
+    assert_eq!(netscape.backfill_titles(), 3);
+
+    let titles: Vec<&str> = netscape
+        .get_bookmarks()
+        .iter()
+        .map(|b| b.title.as_str())
+        .collect();
+
+    assert_eq!(
+        titles,
+        vec!["www.kernel.org", "javascript:alert(1)", "not a url", "Rust"]
+    );
+    assert!(netscape.untitled_bookmarks().is_empty());
+}
+
+#[test]
+fn should_flatten_nested_fixture_below_depth() {
+    let path = Path::new("./res/chromium_nested.html");
+    let mut nested = Netscape::from_file(path).unwrap();
+    let count = nested.get_bookmarks().len();
+
+    nested.flatten_below(2);
+
+    let folders: Vec<&str> = nested
+        .get_folders()
+        .iter()
+        .map(|f| f.title.as_str())
+        .collect();
+    let titles: Vec<&str> = nested
+        .get_bookmarks()
+        .iter()
+        .map(|b| b.title.as_str())
+        .collect();
+
+    assert_eq!(folders, vec!["Bookmarks bar", "Rust", "Empty"]);
+    assert_eq!(titles.len(), count);
+    assert_eq!(
+        titles,
+        vec!["Crates / kuchiki", "Rust", "DuckDuckGo", "Kernel"]
+    );
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_parse_bytes_with_bom() {
     let path = Path::new("./res/chromium.html");
     let chromium = Netscape::from_file(path).unwrap();
+    let bytes = chromium.to_bytes(true).unwrap();
 
-    let imported = sanitize_file(path);
-    let mut parsed = sanitize_string(chromium.to_html().unwrap());
+    assert_eq!(&bytes[..3], UTF8_BOM);
+    assert_eq!(
+        Netscape::from_html(&String::from_utf8(bytes).unwrap()).unwrap(),
+        chromium
+    );
+}
 
-    // the chromium import add a last <p> tag
-    parsed.push_str("<p>");
+#[test]
+fn should_get_bookmarks_by_scheme() {
+    let links = vec![
+        (String::from("alert"), String::from("javascript:alert(1)")),
+        (
+            String::from("Rust"),
+            String::from("https://www.rust-lang.org/"),
+        ),
+        (
+            String::from("local"),
+            String::from("FILE:///home/user/notes.html"),
+        ),
+        (String::from("broken"), String::from("javascript")),
+    ];
+    let netscape = Netscape::from_links("Links", links);
 
-    assert_eq!(imported, parsed)
+    let titles = |scheme: &str| -> Vec<&str> {
+        netscape
+            .bookmarks_by_scheme(scheme)
+            .iter()
+            .map(|b| b.title.as_str())
+            .collect()
+    };
+
+    assert_eq!(titles("JavaScript"), vec!["alert"]);
+    assert_eq!(titles("file"), vec!["local"]);
+    assert!(titles("ftp").is_empty());
 }
 
+#[cfg(feature = "render")]
 #[test]
-fn should_roundtrip_firefox_html() {
-    let path = Path::new("./res/firefox.html");
-    let firefox = Netscape::from_file(path).unwrap();
+fn should_preserve_whitespace_of_unchanged_lines() {
+    let raw = "<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
 
-    let imported = sanitize_file(path);
-    let parsed = sanitize_string(firefox.to_html().unwrap());
+<DL><p>
+\t<DT><H3>Rust</H3>
+\t<DL><p>
+\t\t<DT><A HREF=\"https://www.rust-lang.org/\">Rust</A>
+\t\t<DT><A HREF=\"https://crates.io/\">crates.io</A>
+\t</DL><p>
+</DL><p>
+";
+    let options = ParseOptions {
+        preserve_whitespace: true,
+        ..ParseOptions::default()
+    };
 
-    assert_eq!(parsed, imported)
+    let mut netscape = Netscape::from_html_with(raw, &options).unwrap();
+    assert_eq!(netscape.to_html().unwrap(), raw);
+
+    netscape.bookmarks_mut().last().unwrap().title = String::from("crates");
+    let edited = netscape.to_html().unwrap();
+
+    assert_eq!(
+        edited,
+        raw.replace(
+            "\t\t<DT><A HREF=\"https://crates.io/\">crates.io</A>",
+            "    <DT><A HREF=\"https://crates.io/\" ADD_DATE=\"\">crates</A>"
+        )
+    );
+    assert_ne!(Netscape::from_html(raw).unwrap().to_html().unwrap(), raw);
+
+    for fixture in &["./res/firefox.html", "./res/descriptions.html"] {
+        let path = Path::new(fixture);
+        let preserved = Netscape::from_file_with(path, &options).unwrap();
+
+        assert_eq!(
+            preserved.to_html().unwrap(),
+            std::fs::read_to_string(path).unwrap()
+        );
+    }
+}
+
+#[test]
+fn should_get_favicon_targets_without_icon() {
+    let mut netscape = Netscape::from_links(
+        "Links",
+        vec![
+            (
+                String::from("Rust"),
+                String::from("https://www.rust-lang.org/learn"),
+            ),
+            (
+                String::from("Kernel"),
+                String::from("https://www.kernel.org/"),
+            ),
+            (
+                String::from("mail"),
+                String::from("mailto:someone@example.com"),
+            ),
+            (
+                String::from("Book"),
+                String::from("https://www.rust-lang.org/book"),
+            ),
+        ],
+    );
+    netscape.bookmarks_mut().nth(1).unwrap().icon = String::from("data:image/png;base64,");
+
+    let targets: Vec<(String, &str)> = netscape
+        .favicon_targets()
+        .into_iter()
+        .map(|(url, b)| (url, b.title.as_str()))
+        .collect();
+
+    assert_eq!(
+        targets,
+        vec![
+            (
+                String::from("https://www.rust-lang.org/favicon.ico"),
+                "Rust"
+            ),
+            (
+                String::from("https://www.rust-lang.org/favicon.ico"),
+                "Book"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn should_derive_folder_dates_from_nested_items() {
+    let raw = r#"<DL><p>
+    <DT><H3>Outer</H3>
+    <DL><p>
+        <DT><H3 ADD_DATE="1600910000">Inner</H3>
+        <DL><p>
+            <DT><A HREF="https://www.kernel.org/" ADD_DATE="1600910474">Kernel</A>
+        </DL><p>
+        <DT><A HREF="https://www.rust-lang.org/">Rust</A>
+    </DL><p>
+</DL><p>"#;
+    let mut netscape = Netscape::from_html(raw).unwrap();
+    netscape.derive_folder_dates();
+
+    let folders = netscape.get_folders();
+    assert_eq!(folders[0].add_date, "1600910000");
+    assert_eq!(folders[0].last_modified, "1600910474");
+    assert_eq!(folders[1].add_date, "1600910000");
+    assert_eq!(folders[1].last_modified, "1600910474");
+}
+
+#[test]
+fn should_parse_unclosed_netscape4_items() {
+    let path = Path::new("./res/netscape4_unclosed.html");
+    let netscape = Netscape::from_file(path).unwrap();
+    let titles: Vec<&str> = netscape
+        .get_bookmarks()
+        .iter()
+        .map(|b| b.title.as_str())
+        .collect();
+
+    assert_eq!(
+        titles,
+        vec!["Netscape", "W3C", "AltaVista", "Yahoo!", "Kernel"]
+    );
+    assert_eq!(netscape.get_folders()[0].title, "Search");
+    assert_eq!(netscape.get_folders()[0].children.len(), 2);
+    assert_eq!(
+        netscape.get_bookmarks()[1].description,
+        "The World Wide Web Consortium"
+    );
+    assert_eq!(
+        Netscape::from_html(&std::fs::read_to_string(path).unwrap()).unwrap(),
+        netscape
+    );
+}
+
+#[test]
+fn should_extract_unclosed_netscape4_bookmarks() {
+    let raw = std::fs::read_to_string("./res/netscape4_unclosed.html").unwrap();
+    let extracted = Netscape::extract_bookmarks(&raw);
+
+    assert_eq!(
+        extracted.iter().collect::<Vec<&Bookmark>>(),
+        Netscape::from_html(&raw).unwrap().get_bookmarks()
+    );
+    assert_eq!(extracted.len(), 5);
+    assert_eq!(extracted[0].title, "Netscape");
+}
+
+#[test]
+fn should_find_nested_toolbar_folder_by_title() {
+    let raw = r#"<DL><p>
+    <DT><H3>Other bookmarks</H3>
+    <DL><p>
+        <DT><H3>Bookmarks bar</H3>
+        <DL><p>
+            <DT><A HREF="https://www.kernel.org/">Kernel</A>
+        </DL><p>
+    </DL><p>
+    <DT><H3>favorites BAR</H3>
+    <DL><p>
+    </DL><p>
+</DL><p>"#;
+    let mut netscape = Netscape::from_html(raw).unwrap();
+
+    assert_eq!(netscape.toolbar_folder().unwrap().title, "Bookmarks bar");
+
+    if let Item::Subfolder(f) = &mut netscape.children[1] {
+        f.personal_toolbar_folder = true;
+    }
+
+    assert_eq!(netscape.toolbar_folder().unwrap().title, "favorites BAR");
+}
+
+#[test]
+fn should_reverse_nested_children() {
+    let path = Path::new("./res/chromium_nested.html");
+    let mut nested = Netscape::from_file(path).unwrap();
+    nested.reverse();
+
+    let titles = |children: &[Item]| {
+        children
+            .iter()
+            .map(|i| i.title().to_string())
+            .collect::<Vec<_>>()
+    };
+    let toolbar = nested.children[1].children();
+
+    assert_eq!(titles(&nested.children), vec!["Kernel", "Bookmarks bar"]);
+    assert_eq!(titles(toolbar), vec!["DuckDuckGo", "Empty", "Rust"]);
+    assert_eq!(titles(toolbar[2].children()), vec!["Rust", "Crates"]);
+
+    nested.reverse();
+    assert_eq!(nested, Netscape::from_file(path).unwrap());
+}
+
+#[test]
+fn should_absorb_links_into_existing_domain_folder() {
+    let raw = r#"<DL><p>
+    <DT><H3>Code</H3>
+    <DL><p>
+        <DT><H3>GitHub.com</H3>
+        <DL><p>
+        </DL><p>
+    </DL><p>
+</DL><p>"#;
+    let mut netscape = Netscape::from_html(raw).unwrap();
+    netscape.absorb_links(vec![
+        Bookmark::from(("askama", "https://github.com/djc/askama")),
+        Bookmark::from(("askama", "https://www.github.com/djc/askama/")),
+        Bookmark::from(("notes", "notes.txt")),
+    ]);
+
+    assert_eq!(netscape.children.len(), 2);
+    assert_eq!(netscape.get_folders()[1].children.len(), 1);
+    assert_eq!(netscape.children[1].href(), Some("notes.txt"));
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_keep_value_newlines_with_crlf_line_ending() {
+    use crate::render::LineEnding;
+
+    let netscape = Netscape {
+        children: vec![Item::Shortcut(Bookmark {
+            description: String::from("first\nsecond"),
+            ..Bookmark::from(("a\u{FDD0}n\u{FDD0}", "url"))
+        })],
+        ..Netscape::default()
+    };
+    let options = RenderOptions {
+        line_ending: LineEnding::CrLf,
+        ..RenderOptions::default()
+    };
+    let rendered = netscape.to_html_with(&options).unwrap();
+
+    assert!(rendered.contains("<DD>first\nsecond\r\n"));
+    assert!(rendered.contains(">a\u{FDD0}n\u{FDD0}</A>"));
+    assert_eq!(
+        rendered.matches('\n').count(),
+        rendered.matches("\r\n").count() + 1
+    );
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_roundtrip_markup_in_titles() {
+    let links = vec![
+        (
+            String::from("a <b> & </A> c"),
+            String::from("https://a.b/?x=1&y=\"2\""),
+        ),
+        (
+            String::from("Tom &amp; Jerry"),
+            String::from("https://a.b/?q=&amp;"),
+        ),
+    ];
+    let netscape = Netscape::from_links("Links", links);
+    let rendered = netscape.to_html().unwrap();
+
+    assert!(rendered.contains(r#"HREF="https://a.b/?x=1&y=&quot;2&quot;""#));
+    assert!(rendered.contains(">a &lt;b&gt; &amp; &lt;/A&gt; c</A>"));
+    assert_eq!(Netscape::from_html(&rendered).unwrap(), netscape);
+    assert_eq!(netscape.roundtrip_check(), Ok(()));
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_apply_render_options_on_edited_lines_only() {
+    use crate::render::LineEnding;
+
+    let raw = "<META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\r\n<TITLE>Links</TITLE>\r\n<H1>Links</H1>\r\n<DL><p>\r\n  <DT><A HREF=\"a\" ADD_DATE=\"1\">a</A>\r\n  <DT><A HREF=\"b\" ADD_DATE=\"2\">b</A>\r\n</DL><p>\r\n";
+    let options = ParseOptions {
+        preserve_whitespace: true,
+        ..ParseOptions::default()
+    };
+    let render_options = RenderOptions {
+        line_ending: LineEnding::CrLf,
+        attribute_order: vec![String::from("ADD_DATE")],
+        ..RenderOptions::default()
+    };
+
+    let mut netscape = Netscape::from_html_with(raw, &options).unwrap();
+    assert_eq!(netscape.to_html_with(&render_options).unwrap(), raw);
+
+    netscape.bookmarks_mut().last().unwrap().title = String::from("c");
+    let edited = netscape.to_html_with(&render_options).unwrap();
+
+    assert!(!edited.contains("\r\r\n"));
+    assert_eq!(
+        edited,
+        raw.replace(
+            "  <DT><A HREF=\"b\" ADD_DATE=\"2\">b</A>",
+            "    <DT><A ADD_DATE=\"2\" HREF=\"b\">c</A>"
+        )
+    );
+
+    let mut output = vec![];
+    netscape.write_to(&mut output).unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        netscape.to_html().unwrap()
+    );
+}
+
+#[test]
+fn should_split_url_list_on_urls() {
+    let list = "https://example.com/?a=1|2\nA | B | https://example.com/\nC | https://example.com/?q=a|b\n";
+    let netscape = Netscape::from_url_list(list);
+    let links: Vec<(&str, &str)> = netscape
+        .get_bookmarks()
+        .iter()
+        .map(|b| (b.title.as_str(), b.href.as_str()))
+        .collect();
+
+    assert_eq!(
+        links,
+        vec![
+            ("https://example.com/?a=1|2", "https://example.com/?a=1|2"),
+            ("A | B", "https://example.com/"),
+            ("C", "https://example.com/?q=a|b"),
+        ]
+    );
 }
@@ -1,25 +1,36 @@
+//! Contains the [NodeRefExt] trait that augments the kuchiki nodes.
 use kuchiki::{Attribute, NodeRef};
 
 /// Augments the [NodeRef] struct with conveniant helpers
 pub trait NodeRefExt {
-    fn select_text(&self, selector: &str) -> Option<String>;
+    /// Gets the text contents of the first node matching the selector.
+    fn select_first_text(&self, selector: &str) -> Option<String>;
+
+    /// Collects all the nodes matching the selector, in the document order.
+    fn select_elements(&self, selector: &str) -> Vec<NodeRef>;
+
+    /// Checks if the node is an element with the given tag name, the case is ignored.
     fn is_element(&self, local_name: &str) -> bool;
+
+    /// Gets the attribute of the element with the given name, the case is ignored.
     fn select_attribute(&self, tag_name: &str) -> Option<Attribute>;
+
+    /// Lists all the attributes of the element as `(name, value)` pairs, the names are upper-cased.
+    fn attribute_pairs(&self) -> Vec<(String, String)>;
 }
 
 impl NodeRefExt for NodeRef {
-    fn select_text(&self, selector: &str) -> Option<String> {
-        let mut content = None;
-
-        if let Ok(selection) = self.select(selector) {
-            let nodes = selection.collect::<Vec<_>>();
+    fn select_first_text(&self, selector: &str) -> Option<String> {
+        self.select_elements(selector)
+            .first()
+            .map(|node| node.text_contents())
+    }
 
-            if let Some(child) = nodes[0].as_node().first_child() {
-                content = Some(child.text_contents());
-            }
+    fn select_elements(&self, selector: &str) -> Vec<NodeRef> {
+        match self.select(selector) {
+            Ok(selection) => selection.map(|n| n.as_node().clone()).collect(),
+            Err(_) => vec![],
         }
-
-        content
     }
 
     fn is_element(&self, tag_name: &str) -> bool {
@@ -51,6 +62,33 @@ impl NodeRefExt for NodeRef {
 
         attribute
     }
+
+    fn attribute_pairs(&self) -> Vec<(String, String)> {
+        let mut pairs = vec![];
+
+        if let Some(element) = self.as_element() {
+            let attributes = element.attributes.borrow();
+
+            for (exp_name, attr) in &attributes.map {
+                pairs.push((
+                    exp_name.local.to_ascii_uppercase().to_string(),
+                    attr.value.clone(),
+                ));
+            }
+        }
+
+        pairs
+    }
+}
+
+/// Gets a date attribute of an `<A/>` or `<H3/>` element, falling back on its enclosing `<DT/>` element.
+/// Some non-standard exports set the dates on the `<DT/>` element instead of its child.
+pub(crate) fn select_date_attribute(node: &NodeRef, attribute_name: &str) -> Option<Attribute> {
+    node.select_attribute(attribute_name).or_else(|| {
+        node.parent()
+            .filter(|parent| parent.is_element("DT"))
+            .and_then(|dt| dt.select_attribute(attribute_name))
+    })
 }
 
 #[test]
@@ -60,8 +98,8 @@ fn should_check_dl_element() {
 
     let dl = parse_html().one(r"<DL></DL>").select_first("DL").unwrap();
 
-    assert_eq!(dl.as_node().is_element("DL"), true);
-    assert_eq!(dl.as_node().is_element("DT"), false);
+    assert!(dl.as_node().is_element("DL"));
+    assert!(!dl.as_node().is_element("DT"));
 }
 
 #[test]
@@ -89,10 +127,42 @@ fn should_ignore_selector_case() {
     let capital = r"<Title>Test</Title>";
     let lower = r"<title>Test</title>";
 
-    let from_upper = parse_html().one(upper).select_text(selector).unwrap();
-    let from_capital = parse_html().one(capital).select_text(selector).unwrap();
-    let from_lower = parse_html().one(lower).select_text(selector).unwrap();
+    let from_upper = parse_html().one(upper).select_first(selector).unwrap();
+    let from_capital = parse_html().one(capital).select_first(selector).unwrap();
+    let from_lower = parse_html().one(lower).select_first(selector).unwrap();
+
+    assert_eq!(from_upper.text_contents(), from_capital.text_contents());
+    assert_eq!(from_capital.text_contents(), from_lower.text_contents());
+}
+
+#[test]
+fn should_list_attribute_pairs() {
+    use kuchiki::parse_html;
+    use kuchiki::traits::TendrilSink;
+
+    let a = parse_html()
+        .one(r#"<A HREF="Test" data-id="1"/>"#)
+        .select_first("A")
+        .unwrap();
+
+    assert_eq!(
+        a.as_node().attribute_pairs(),
+        vec![
+            (String::from("DATA-ID"), String::from("1")),
+            (String::from("HREF"), String::from("Test")),
+        ]
+    )
+}
+
+#[test]
+fn should_select_elements_and_text() {
+    use kuchiki::parse_html;
+    use kuchiki::traits::TendrilSink;
+
+    let document = parse_html().one(r"<DL><DT><A>one<b>!</b></A><DT><A>two</A></DL>");
 
-    assert_eq!(from_upper, from_capital);
-    assert_eq!(from_capital, from_lower);
+    assert_eq!(document.select_elements("A").len(), 2);
+    assert!(document.select_elements("!invalid").is_empty());
+    assert_eq!(document.select_first_text("A").unwrap(), "one!");
+    assert_eq!(document.select_first_text("H3"), None);
 }
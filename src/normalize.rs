@@ -0,0 +1,111 @@
+//! Contains the normalization of the urls, to compare the bookmarks pointing at the same page, and their cleanup.
+use url::form_urlencoded;
+use url::Url;
+
+/// The query parameters stripped by default, the names ending with `*` are prefixes.
+pub(crate) const TRACKING_PARAMS: [&str; 3] = ["utm_*", "fbclid", "gclid"];
+
+/// Normalizes a `href` : the host is lowercased without its `www.` prefix, the default port
+/// and the trailing slash of the path are removed. The malformed urls are only trimmed.
+pub(crate) fn normalized_url(href: &str) -> String {
+    let mut url = match Url::parse(href.trim()) {
+        Ok(url) => url,
+        Err(_) => return href.trim().to_string(),
+    };
+
+    if let Some(host) = url.host_str().map(str::to_lowercase) {
+        if let Some(stripped) = host.strip_prefix("www.") {
+            let _ = url.set_host(Some(stripped));
+        }
+    }
+
+    let mut normalized = url.to_string();
+
+    if url.query().is_none() && url.fragment().is_none() {
+        while normalized.ends_with('/') && !normalized.ends_with("://") {
+            normalized.pop();
+        }
+    } else {
+        let path = url.path().trim_end_matches('/').to_string();
+        url.set_path(&path);
+        normalized = url.to_string();
+    }
+
+    normalized
+}
+
+/// Removes the query parameters named by the `params` from a `href`, a name ending with `*` is a prefix.
+/// The other parameters are kept as they are written, it is [None] when the url is malformed or unchanged.
+pub(crate) fn strip_query_params(href: &str, params: &[&str]) -> Option<String> {
+    let mut url = Url::parse(href).ok()?;
+    let query = url.query()?.to_string();
+
+    let matches = |name: &str| {
+        params.iter().any(|param| match param.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == *param,
+        })
+    };
+
+    let kept: Vec<&str> = query
+        .split('&')
+        .filter(|pair| {
+            let name = form_urlencoded::parse(pair.as_bytes())
+                .next()
+                .map(|(name, _)| name.to_string())
+                .unwrap_or_default();
+
+            !matches(&name)
+        })
+        .collect();
+
+    if kept.len() == query.split('&').count() {
+        return None;
+    }
+
+    if kept.is_empty() {
+        url.set_query(None);
+    } else {
+        url.set_query(Some(&kept.join("&")));
+    }
+
+    Some(url.to_string())
+}
+
+#[test]
+fn should_strip_query_params() {
+    assert_eq!(
+        strip_query_params(
+            "https://kernel.org/doc?utm_source=feed&q=a+b&fbclid=1#top",
+            &TRACKING_PARAMS
+        ),
+        Some(String::from("https://kernel.org/doc?q=a+b#top"))
+    );
+    assert_eq!(
+        strip_query_params("https://kernel.org/?gclid=1", &TRACKING_PARAMS),
+        Some(String::from("https://kernel.org/"))
+    );
+    assert_eq!(
+        strip_query_params("https://kernel.org/?q=1", &TRACKING_PARAMS),
+        None
+    );
+    assert_eq!(
+        strip_query_params("not a url?utm_source=1", &TRACKING_PARAMS),
+        None
+    );
+}
+
+#[test]
+fn should_normalize_equivalent_urls() {
+    let expected = "https://kernel.org";
+
+    assert_eq!(normalized_url("https://www.kernel.org/"), expected);
+    assert_eq!(normalized_url("https://WWW.Kernel.ORG:443"), expected);
+    assert_eq!(normalized_url(" https://kernel.org "), expected);
+    assert_eq!(
+        normalized_url("https://kernel.org/doc/?q=1"),
+        "https://kernel.org/doc?q=1"
+    );
+    assert_ne!(normalized_url("http://kernel.org/"), expected);
+    assert_eq!(normalized_url("not a url"), "not a url");
+}
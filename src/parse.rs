@@ -0,0 +1,81 @@
+//! Contains the [ParseOptions] that customize the parsing of a [Netscape] document.
+//!
+//! [Netscape]: crate::Netscape
+use url::Url;
+
+/// Stores the options available to parse a [Netscape] document.
+///
+/// [Netscape]: crate::Netscape
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions {
+    /// The `allowed_schemes` attribute lists the url schemes of the kept bookmarks, e.g. `["http", "https"]`.
+    /// When it is set, the bookmarks with another scheme or with a malformed `href` are skipped.
+    /// All the bookmarks are kept by default.
+    pub allowed_schemes: Option<Vec<String>>,
+
+    /// The `default_title` attribute fills the `title` and the `h1` of a document that has neither, e.g. its file name.
+    /// The missing titles are left empty by default.
+    pub default_title: Option<String>,
+
+    /// The `skip_place_queries` attribute skips the bookmarks whose `href` has the `place:` scheme,
+    /// i.e. the Firefox smart bookmarks that query the history, they aren't real urls. They are kept by default.
+    pub skip_place_queries: bool,
+
+    /// The `preserve_whitespace` attribute keeps the raw text of the document in its `source`,
+    /// so that its unchanged lines are rendered with their original whitespace, see [PreservedSource].
+    /// It costs about twice the size of the document in memory, it is disabled by default.
+    /// It only applies to the documents parsed from their text, not from an already parsed DOM,
+    /// and it needs the `render` feature : the documents keep no `source` without it.
+    ///
+    /// [PreservedSource]: crate::PreservedSource
+    pub preserve_whitespace: bool,
+}
+
+impl ParseOptions {
+    /// Checks if a bookmark with the given `href` should be kept.
+    pub(crate) fn allows_href(&self, href: &str) -> bool {
+        if self.skip_place_queries && is_place_query(href) {
+            return false;
+        }
+
+        match &self.allowed_schemes {
+            None => true,
+            Some(schemes) => Url::parse(href)
+                .is_ok_and(|url| schemes.iter().any(|s| s.eq_ignore_ascii_case(url.scheme()))),
+        }
+    }
+}
+
+/// Checks if a `href` is a Firefox query, e.g. `place:sort=8&maxResults=10`.
+pub(crate) fn is_place_query(href: &str) -> bool {
+    href.trim()
+        .get(..6)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("place:"))
+}
+
+#[test]
+fn should_skip_place_queries() {
+    let options = ParseOptions {
+        skip_place_queries: true,
+        ..ParseOptions::default()
+    };
+
+    assert!(!options.allows_href("place:sort=8&maxResults=10"));
+    assert!(!options.allows_href(" PLACE:folder=TOOLBAR"));
+    assert!(options.allows_href("https://www.kernel.org/"));
+    assert!(ParseOptions::default().allows_href("place:sort=8"));
+}
+
+#[test]
+fn should_allow_listed_schemes() {
+    let options = ParseOptions {
+        allowed_schemes: Some(vec![String::from("HTTPS")]),
+        ..ParseOptions::default()
+    };
+
+    assert!(options.allows_href("https://www.kernel.org/"));
+    assert!(!options.allows_href("javascript:alert(1)"));
+    assert!(!options.allows_href("data:text/html,hello"));
+    assert!(!options.allows_href("relative/path"));
+    assert!(ParseOptions::default().allows_href("javascript:alert(1)"));
+}
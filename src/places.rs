@@ -0,0 +1,176 @@
+//! Contains the import of the bookmarks of a Firefox profile from its `places.sqlite` database.
+use rusqlite::{Connection, OpenFlags};
+use std::collections::HashMap;
+use std::io::Error;
+use std::path::Path;
+
+use crate::item::Item;
+use crate::{Bookmark, Folder, Netscape};
+
+/// The `type` of the bookmark rows in the `moz_bookmarks` table.
+const TYPE_BOOKMARK: i64 = 1;
+
+/// The `type` of the folder rows in the `moz_bookmarks` table.
+const TYPE_FOLDER: i64 = 2;
+
+/// Represents a row of the `moz_bookmarks` table, joined with its `moz_places` url.
+struct Row {
+    id: i64,
+    kind: i64,
+    guid: String,
+    title: String,
+    url: String,
+    date_added: Option<i64>,
+    last_modified: Option<i64>,
+    last_visit: Option<i64>,
+}
+
+/// Reads the bookmarks of a `places.sqlite` database, the database is opened read-only.
+///
+/// The content of the menu is imported at the root, as in the Firefox exports,
+/// followed by the toolbar and the unfiled folders. The tags and the separators are ignored.
+pub(crate) fn from_places_sqlite(path: &Path) -> Result<Netscape, Error> {
+    let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(Error::other)?;
+
+    let mut children_of: HashMap<i64, Vec<Row>> = HashMap::new();
+    let mut root = None;
+
+    for (parent, row) in select_rows(&connection).map_err(Error::other)? {
+        if parent == 0 {
+            root = Some(row.id);
+        } else {
+            children_of.entry(parent).or_default().push(row);
+        }
+    }
+
+    let mut children = vec![];
+
+    if let Some(root) = root {
+        for row in children_of.remove(&root).unwrap_or_default() {
+            match row.guid.as_str() {
+                "menu________" => children.extend(build_children(row.id, &mut children_of)),
+                "tags________" => {}
+                _ => children.push(build_item(row, &mut children_of)),
+            }
+        }
+    }
+
+    Ok(Netscape {
+        title: String::from("Bookmarks"),
+        h1: String::from("Bookmarks Menu"),
+        children,
+        ..Netscape::default()
+    })
+}
+
+fn select_rows(connection: &Connection) -> rusqlite::Result<Vec<(i64, Row)>> {
+    let mut statement = connection.prepare(
+        "SELECT b.id, b.parent, b.type, b.guid, b.title, p.url, b.dateAdded, b.lastModified, p.last_visit_date
+         FROM moz_bookmarks b LEFT JOIN moz_places p ON b.fk = p.id
+         ORDER BY b.parent, b.position",
+    )?;
+
+    let rows = statement.query_map([], |row| {
+        Ok((
+            row.get(1)?,
+            Row {
+                id: row.get(0)?,
+                kind: row.get(2)?,
+                guid: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                title: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+                url: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+                date_added: row.get(6)?,
+                last_modified: row.get(7)?,
+                last_visit: row.get(8)?,
+            },
+        ))
+    })?;
+
+    rows.collect()
+}
+
+fn build_children(id: i64, children_of: &mut HashMap<i64, Vec<Row>>) -> Vec<Item> {
+    children_of
+        .remove(&id)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|row| row.kind == TYPE_BOOKMARK || row.kind == TYPE_FOLDER)
+        .map(|row| build_item(row, children_of))
+        .collect()
+}
+
+fn build_item(row: Row, children_of: &mut HashMap<i64, Vec<Row>>) -> Item {
+    if row.kind == TYPE_FOLDER {
+        Item::Subfolder(Folder {
+            add_date: seconds(row.date_added),
+            last_modified: seconds(row.last_modified),
+            personal_toolbar_folder: row.guid == "toolbar_____",
+            unfiled_bookmarks_folder: row.guid == "unfiled_____",
+            children: build_children(row.id, children_of),
+            title: row.title,
+            ..Folder::default()
+        })
+    } else {
+        Item::Shortcut(Bookmark {
+            href: row.url,
+            title: row.title,
+            add_date: seconds(row.date_added),
+            last_visit: seconds(row.last_visit),
+            last_modified: seconds(row.last_modified),
+            ..Bookmark::default()
+        })
+    }
+}
+
+/// Converts the microseconds timestamps of Firefox into the seconds timestamps of the Netscape format.
+/// The `0` dates, that Firefox writes for the missing ones, are empty.
+fn seconds(microseconds: Option<i64>) -> String {
+    microseconds
+        .map(|us| us / 1_000_000)
+        .filter(|seconds| *seconds > 0)
+        .map(|seconds| seconds.to_string())
+        .unwrap_or_default()
+}
+
+#[test]
+fn should_import_places_sqlite() {
+    let path = std::env::temp_dir().join("bookmarkt_places.sqlite");
+    let _ = std::fs::remove_file(&path);
+
+    let connection = Connection::open(&path).unwrap();
+    connection
+        .execute_batch(
+            "CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url TEXT, title TEXT, last_visit_date INTEGER);
+             CREATE TABLE moz_bookmarks (id INTEGER PRIMARY KEY, type INTEGER, fk INTEGER, parent INTEGER,
+                 position INTEGER, title TEXT, dateAdded INTEGER, lastModified INTEGER, guid TEXT);
+             INSERT INTO moz_places VALUES (1, 'https://www.rust-lang.org/', 'Rust', 1600910527000000);
+             INSERT INTO moz_places VALUES (2, 'https://crates.io/', 'crates.io', NULL);
+             INSERT INTO moz_bookmarks VALUES (1, 2, NULL, 0, 0, '', 0, 0, 'root________');
+             INSERT INTO moz_bookmarks VALUES (2, 2, NULL, 1, 0, 'menu', 0, 0, 'menu________');
+             INSERT INTO moz_bookmarks VALUES (3, 2, NULL, 1, 1, 'toolbar', 1600910474000000, 0, 'toolbar_____');
+             INSERT INTO moz_bookmarks VALUES (4, 2, NULL, 1, 2, 'tags', 0, 0, 'tags________');
+             INSERT INTO moz_bookmarks VALUES (5, 1, 2, 2, 0, 'crates.io', 1600910553000000, 0, 'a');
+             INSERT INTO moz_bookmarks VALUES (6, 3, NULL, 2, 1, NULL, 0, 0, 'b');
+             INSERT INTO moz_bookmarks VALUES (7, 1, 1, 3, 0, 'Rust', 1600910526000000, 0, 'c');",
+        )
+        .unwrap();
+    drop(connection);
+
+    let netscape = from_places_sqlite(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    match &netscape.children[..] {
+        [Item::Shortcut(crates), Item::Subfolder(toolbar)] => {
+            assert_eq!(crates.href, "https://crates.io/");
+            assert_eq!(crates.add_date, "1600910553");
+            assert!(crates.last_modified.is_empty());
+            assert!(toolbar.personal_toolbar_folder);
+            assert_eq!(toolbar.add_date, "1600910474");
+            assert!(
+                matches!(&toolbar.children[..], [Item::Shortcut(rust)] if rust.last_visit == "1600910527")
+            );
+        }
+        _ => panic!("unexpected children"),
+    }
+}
@@ -0,0 +1,117 @@
+//! Contains the import of the Pocket HTML exports, a flat `<ul/>` list of links instead of a Netscape tree.
+use std::io::{Error, ErrorKind};
+
+use crate::item::Item;
+use crate::lenient::parse_document;
+use crate::node_ref_ext::*;
+use crate::{Bookmark, Folder, Netscape};
+
+/// Reads the links of a Pocket export, with their `time_added` as `add_date`, the `0` dates are empty.
+/// When `group_by_tags` is set, the bookmarks are copied into a [Folder] per tag, the untagged ones stay at the root.
+pub(crate) fn from_pocket(raw: &str, group_by_tags: bool) -> Result<Netscape, Error> {
+    let document = parse_document(raw);
+    let links = document.select_elements("ul > li > a");
+
+    if links.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "the document has no Pocket list of links",
+        ));
+    }
+
+    let mut folders: Vec<Folder> = vec![];
+    let mut children = vec![];
+
+    for a in links {
+        let attribute = |name: &str| {
+            a.select_attribute(name)
+                .map(|attribute| attribute.value)
+                .unwrap_or_default()
+        };
+
+        let bookmark = Bookmark {
+            href: attribute("HREF"),
+            title: a.text_contents(),
+            add_date: Some(attribute("TIME_ADDED"))
+                .filter(|date| date != "0")
+                .unwrap_or_default(),
+            ..Bookmark::default()
+        };
+
+        let tags = attribute("TAGS");
+        let tags: Vec<&str> = tags
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .collect();
+
+        if !group_by_tags || tags.is_empty() {
+            children.push(Item::Shortcut(bookmark));
+            continue;
+        }
+
+        for tag in tags {
+            let index = match folders.iter().position(|f| f.title == tag) {
+                Some(index) => index,
+                None => {
+                    folders.push(Folder {
+                        title: String::from(tag),
+                        ..Folder::default()
+                    });
+                    folders.len() - 1
+                }
+            };
+
+            folders[index]
+                .children
+                .push(Item::Shortcut(bookmark.clone()));
+        }
+    }
+
+    let mut items: Vec<Item> = folders.into_iter().map(Item::Subfolder).collect();
+    items.append(&mut children);
+
+    let title = document.select_first_text("TITLE").unwrap_or_default();
+
+    Ok(Netscape {
+        h1: title.clone(),
+        title,
+        children: items,
+        ..Netscape::default()
+    })
+}
+
+#[test]
+fn should_group_pocket_links_by_tags() {
+    let raw = r#"<!DOCTYPE html>
+<html><head><title>Pocket Export</title></head>
+<body>
+<h1>Unread</h1>
+<ul>
+<li><a href="https://www.rust-lang.org/" time_added="1600910526" tags="rust,dev">Rust</a></li>
+<li><a href="https://www.kernel.org/" time_added="0" tags="">Kernel</a></li>
+</ul>
+<h1>Read Archive</h1>
+<ul>
+<li><a href="https://crates.io/" time_added="1600910600" tags="rust">crates.io</a></li>
+</ul>
+</body></html>"#;
+
+    let flat = from_pocket(raw, false).unwrap();
+    let grouped = from_pocket(raw, true).unwrap();
+
+    assert_eq!(flat.title, "Pocket Export");
+    assert_eq!(flat.children.len(), 3);
+    assert_eq!(flat.get_bookmarks()[0].add_date, "1600910526");
+    assert_eq!(flat.get_bookmarks()[1].add_date, "");
+
+    let folders: Vec<(&str, usize)> = grouped
+        .get_folders()
+        .iter()
+        .map(|f| (f.title.as_str(), f.children.len()))
+        .collect();
+
+    assert_eq!(folders, vec![("rust", 2), ("dev", 1)]);
+    assert_eq!(grouped.children.len(), 3);
+    assert!(from_pocket("<p>nothing</p>", false).is_err());
+}
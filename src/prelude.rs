@@ -0,0 +1,32 @@
+//! Re-exports the models and their builders, so that `use bookmarkt::prelude::*;` brings them into scope.
+//!
+//! ```rust
+//! use bookmarkt::prelude::*;
+//!
+//! let bookmark = BookmarkBuilder::default()
+//!     .href("https://www.kernel.org/")
+//!     .title("The Linux Kernel Archives")
+//!     .build()
+//!     .unwrap();
+//!
+//! let folder = FolderBuilder::default()
+//!     .title("Linux")
+//!     .children(vec![Item::Shortcut(bookmark)])
+//!     .build()
+//!     .unwrap();
+//!
+//! let netscape: Netscape = NetscapeBuilder::default()
+//!     .title("Bookmarks")
+//!     .add_folder(folder)
+//!     .build()
+//!     .unwrap();
+//!
+//! assert_eq!(netscape.get_bookmarks().len(), 1);
+//! ```
+pub use crate::{Bookmark, BookmarkBuilder, BookmarkKey};
+pub use crate::{BrowserHint, ChangePreview, Item, LintIssue, MergeStrategy, PreservedSource};
+#[cfg(feature = "render")]
+pub use crate::{EscapePolicy, LineEnding, RenderOptions};
+pub use crate::{Folder, FolderBuilder};
+pub use crate::{Netscape, NetscapeBuilder};
+pub use crate::{ParseOptions, Renderer, Stats};
@@ -0,0 +1,249 @@
+//! Contains the [PreservedSource] that reproduces the whitespace of a parsed document when it is rendered again.
+#[cfg(feature = "render")]
+use crate::render::RenderOptions;
+
+/// The maximum size of the table of the line alignment, the larger differences are rendered again.
+#[cfg(feature = "render")]
+const MAX_ALIGNMENT_CELLS: usize = 1 << 20;
+
+/// Stores the raw text of a parsed document, and its rendering right after the parsing.
+///
+/// When the document is rendered again, the lines whose rendering didn't change are copied from the raw text,
+/// with their original whitespace. The other lines are the ones of the new rendering.
+/// An unchanged document is rendered byte for byte as its raw text.
+///
+/// It keeps both texts in memory, i.e. about twice the size of the file for every preserving document.
+/// Without the `render` feature, it is empty and the documents never keep it.
+#[derive(Clone, Debug, Default)]
+pub struct PreservedSource {
+    #[cfg(feature = "render")]
+    raw: String,
+    #[cfg(feature = "render")]
+    rendered: String,
+}
+
+#[cfg(feature = "render")]
+impl PreservedSource {
+    /// Creates a [PreservedSource] from the `raw` text of a document, and its `rendered` model.
+    pub(crate) fn new(raw: &str, rendered: String) -> Self {
+        PreservedSource {
+            raw: raw.to_string(),
+            rendered,
+        }
+    }
+
+    /// Replaces the lines of the new `rendered` document with their raw text, when they are unchanged.
+    ///
+    /// The raw and the rendered lines are matched by their tags and their text, regardless of the attributes.
+    /// The raw lines that aren't rendered, i.e. the blank lines and the comments, are kept around the copied lines.
+    /// The other raw lines, e.g. an item split over several lines, are only kept when their item is unchanged.
+    /// The rendered lines without any element, e.g. the generated header comment, are only added when they changed.
+    /// The `options` only apply on the rendered lines, the raw lines are copied with their own line endings.
+    /// The `output` is the same rendering with the `attribute_order` of the `options`, its lines replace the ones
+    /// of `rendered`, whose order is the one of the original rendering.
+    pub(crate) fn apply(&self, rendered: &str, output: &str, options: &RenderOptions) -> String {
+        if rendered == self.rendered {
+            return self.raw.clone();
+        }
+
+        let raw: Vec<&str> = self.raw.split_inclusive('\n').collect();
+        let original: Vec<&str> = self.rendered.split_inclusive('\n').collect();
+        let current: Vec<&str> = rendered.split_inclusive('\n').collect();
+        let output: Vec<&str> = output.split_inclusive('\n').collect();
+
+        if output.len() != current.len() {
+            return options.apply(&output.concat());
+        }
+
+        let raw_of = align(
+            &original
+                .iter()
+                .map(|line| signature(line))
+                .collect::<Vec<_>>(),
+            &raw.iter().map(|line| signature(line)).collect::<Vec<_>>(),
+        );
+        let original_of = align(&current, &original);
+
+        let mut preserved = String::with_capacity(rendered.len());
+        let mut next_raw = 0;
+
+        for (index, line) in current.iter().enumerate() {
+            match original_of[index].map(|o| raw_of[o]) {
+                Some(Some(r)) if r >= next_raw => {
+                    for line in &raw[next_raw..r] {
+                        if is_trivia(line) {
+                            preserved.push_str(line);
+                        }
+                    }
+
+                    preserved.push_str(raw[r]);
+                    next_raw = r + 1;
+
+                    if !raw[r].ends_with('\n') && index + 1 < current.len() {
+                        preserved.push_str(options.line_ending.as_str());
+                    }
+                }
+                Some(None) if !signature(line).contains('<') => {}
+                _ => {
+                    while next_raw < raw.len() && is_trivia(raw[next_raw]) {
+                        preserved.push_str(raw[next_raw]);
+                        next_raw += 1;
+                    }
+
+                    options.apply_line(output[index], &mut preserved);
+                }
+            }
+        }
+
+        for line in &raw[next_raw..] {
+            if is_trivia(line) {
+                preserved.push_str(line);
+            }
+        }
+
+        preserved
+    }
+}
+
+/// Checks if a raw line isn't rendered, i.e. it is blank or a single-line comment.
+#[cfg(feature = "render")]
+fn is_trivia(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || (trimmed.starts_with("<!--") && trimmed.ends_with("-->"))
+}
+
+/// Reduces a line to its tag names and its text, e.g. `<DT><A>Rust</A>` : the attributes, the whitespace,
+/// the `<p>` tags and the declarations or comments are ignored, the tag names are upper-cased.
+#[cfg(feature = "render")]
+fn signature(line: &str) -> String {
+    let mut signature = String::new();
+    let mut rest = line;
+
+    while let Some(start) = rest.find('<') {
+        signature.push_str(rest[..start].trim());
+
+        let tag = &rest[start + 1..];
+        let end = tag.find('>').map_or(tag.len(), |end| end + 1);
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(char::is_ascii_alphanumeric)
+            .collect();
+
+        if !name.is_empty() && !name.eq_ignore_ascii_case("P") {
+            signature.push('<');
+
+            if closing {
+                signature.push('/');
+            }
+
+            signature.push_str(&name.to_ascii_uppercase());
+            signature.push('>');
+        }
+
+        rest = &tag[end..];
+    }
+
+    signature.push_str(rest.trim());
+    signature
+}
+
+/// Aligns the lines of `from` with the lines of `to` along their longest common subsequence.
+/// The common prefix and suffix are aligned first, then the remaining lines if their table is small enough.
+#[cfg(feature = "render")]
+fn align<T: PartialEq>(from: &[T], to: &[T]) -> Vec<Option<usize>> {
+    let mut aligned = vec![None; from.len()];
+
+    let mut prefix = 0;
+    while prefix < from.len() && prefix < to.len() && from[prefix] == to[prefix] {
+        aligned[prefix] = Some(prefix);
+        prefix += 1;
+    }
+
+    let mut suffix = 0;
+    while suffix < from.len() - prefix
+        && suffix < to.len() - prefix
+        && from[from.len() - 1 - suffix] == to[to.len() - 1 - suffix]
+    {
+        aligned[from.len() - 1 - suffix] = Some(to.len() - 1 - suffix);
+        suffix += 1;
+    }
+
+    let from_middle = &from[prefix..from.len() - suffix];
+    let to_middle = &to[prefix..to.len() - suffix];
+
+    if from_middle.is_empty()
+        || to_middle.is_empty()
+        || (from_middle.len() + 1) * (to_middle.len() + 1) > MAX_ALIGNMENT_CELLS
+    {
+        return aligned;
+    }
+
+    let width = to_middle.len() + 1;
+    let mut lengths = vec![0u32; (from_middle.len() + 1) * width];
+
+    for i in (0..from_middle.len()).rev() {
+        for j in (0..to_middle.len()).rev() {
+            lengths[i * width + j] = if from_middle[i] == to_middle[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+
+    while i < from_middle.len() && j < to_middle.len() {
+        if from_middle[i] == to_middle[j] {
+            aligned[prefix + i] = Some(prefix + j);
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    aligned
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_align_common_lines() {
+    let from = ["a\n", "b\n", "c\n", "d\n"];
+    let to = ["a\n", "x\n", "c\n", "b\n", "d\n"];
+
+    assert_eq!(align(&from, &to), vec![Some(0), None, Some(2), Some(4)]);
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_reduce_lines_to_signatures() {
+    assert_eq!(
+        signature("  <dt><a href=\"url\" ADD_DATE=\"\">Rust</a>\r\n"),
+        "<DT><A>Rust</A>"
+    );
+    assert_eq!(signature("</DL><p>\n"), "</DL>");
+    assert_eq!(signature("<!-- DO NOT EDIT! -->"), "");
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_keep_raw_lines_of_unchanged_items() {
+    let raw = "<DL><p>\n\n  <DT><A HREF=\"a\">a</A>\r\n\t<!-- note -->\n  <DT><A HREF=\"b\">b</A>\n</DL><p>\n";
+    let rendered = "<DL><p>\n    <DT><A HREF=\"a\">a</A>\n    <DT><A HREF=\"b\">b</A>\n</DL><p>\n";
+    let source = PreservedSource::new(raw, String::from(rendered));
+
+    let options = RenderOptions::default();
+
+    let edited = rendered.replace(">b<", ">c<");
+
+    assert_eq!(source.apply(rendered, rendered, &options), raw);
+    assert_eq!(
+        source.apply(&edited, &edited, &options),
+        "<DL><p>\n\n  <DT><A HREF=\"a\">a</A>\r\n\t<!-- note -->\n    <DT><A HREF=\"b\">c</A>\n</DL><p>\n"
+    );
+}
@@ -0,0 +1,382 @@
+//! Contains the [ChangePreview] that describes the changes of a mutation without applying it.
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+
+use crate::item::Item;
+use crate::merge::{attributes, same_attributes};
+use crate::{Bookmark, Folder};
+
+/// Describes the changes that a mutation would apply to a document, see [Netscape::preview].
+///
+/// The `path` of every change lists the folder titles from the root of the document to the item.
+/// The bookmarks of both documents are paired, first with their identical copy, then by their `href`.
+/// A paired bookmark is modified when any of its attributes differs, and moved when its folder changes
+/// or when it isn't in the same order than the other paired bookmarks anymore, e.g. after a sort.
+/// The folders are paired by their path, and compared by their own attributes.
+///
+/// [Netscape::preview]: crate::Netscape::preview
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ChangePreview {
+    /// The `removed_bookmarks` are the bookmarks missing after the mutation, with their path.
+    pub removed_bookmarks: Vec<(Vec<String>, Bookmark)>,
+
+    /// The `added_bookmarks` are the bookmarks that appear after the mutation, with their path.
+    pub added_bookmarks: Vec<(Vec<String>, Bookmark)>,
+
+    /// The `modified_bookmarks` are the `(path, before, after)` bookmarks whose attributes change.
+    /// The `path` is the one after the mutation.
+    pub modified_bookmarks: Vec<(Vec<String>, Bookmark, Bookmark)>,
+
+    /// The `moved_bookmarks` are the `(old path, new path, bookmark)` bookmarks that change their place.
+    /// The `bookmark` has its attributes after the mutation.
+    pub moved_bookmarks: Vec<(Vec<String>, Vec<String>, Bookmark)>,
+
+    /// The `removed_folders` are the paths of the folders missing after the mutation, their own title included.
+    pub removed_folders: Vec<Vec<String>>,
+
+    /// The `added_folders` are the paths of the folders that appear after the mutation, their own title included.
+    pub added_folders: Vec<Vec<String>>,
+
+    /// The `modified_folders` are the paths of the folders whose own attributes change, e.g. `folded`.
+    pub modified_folders: Vec<Vec<String>>,
+
+    /// The `moved_folders` are the paths of the folders that aren't in the same order among their siblings.
+    pub moved_folders: Vec<Vec<String>>,
+}
+
+impl ChangePreview {
+    /// Compares the `before` and `after` children of a document, in the document order.
+    pub(crate) fn between(before: &[Item], after: &[Item]) -> Self {
+        let mut preview = ChangePreview::default();
+
+        let before_bookmarks = bookmarks_with_path(before, &mut vec![]);
+        let after_bookmarks = bookmarks_with_path(after, &mut vec![]);
+        let (pairs, removed, added) = pair_bookmarks(&before_bookmarks, &after_bookmarks);
+        let ordered = in_order(&pairs);
+
+        for (&(b, a), ordered) in pairs.iter().zip(ordered) {
+            let ((old_path, old), (new_path, new)) = (&before_bookmarks[b], &after_bookmarks[a]);
+
+            if old.href != new.href || !same_attributes(old, new) {
+                preview
+                    .modified_bookmarks
+                    .push((new_path.clone(), old.clone(), new.clone()));
+            }
+
+            if old_path != new_path || !ordered {
+                preview
+                    .moved_bookmarks
+                    .push((old_path.clone(), new_path.clone(), new.clone()));
+            }
+        }
+
+        preview.removed_bookmarks = removed
+            .into_iter()
+            .map(|b| before_bookmarks[b].clone())
+            .collect();
+        preview.added_bookmarks = added
+            .into_iter()
+            .map(|a| after_bookmarks[a].clone())
+            .collect();
+
+        let before_folders = folders_with_path(before, &mut vec![]);
+        let after_folders = folders_with_path(after, &mut vec![]);
+        let (pairs, removed, added) =
+            pair_by(&before_folders, &after_folders, |(path, _)| path.clone());
+        let ordered = in_order(&pairs);
+
+        for (&(b, a), ordered) in pairs.iter().zip(ordered) {
+            let ((path, old), (_, new)) = (&before_folders[b], &after_folders[a]);
+
+            if !same_folder_attributes(old, new) {
+                preview.modified_folders.push(path.clone());
+            }
+
+            if !ordered {
+                preview.moved_folders.push(path.clone());
+            }
+        }
+
+        preview.removed_folders = removed
+            .into_iter()
+            .map(|b| before_folders[b].0.clone())
+            .collect();
+        preview.added_folders = added
+            .into_iter()
+            .map(|a| after_folders[a].0.clone())
+            .collect();
+
+        preview
+    }
+
+    /// Checks if the mutation wouldn't change anything.
+    pub fn is_empty(&self) -> bool {
+        self.removed_bookmarks.is_empty()
+            && self.added_bookmarks.is_empty()
+            && self.modified_bookmarks.is_empty()
+            && self.moved_bookmarks.is_empty()
+            && self.removed_folders.is_empty()
+            && self.added_folders.is_empty()
+            && self.modified_folders.is_empty()
+            && self.moved_folders.is_empty()
+    }
+}
+
+/// Lists the changes, one per line, e.g. `- Bookmarks bar / Rust: https://www.rust-lang.org/`.
+/// The lines start with `-` for the removals, `+` for the additions, `~` for the modifications and `>` for the moves.
+impl fmt::Display for ChangePreview {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for path in self.removed_folders.iter() {
+            writeln!(f, "- {}/", path.join(" / "))?;
+        }
+
+        for path in self.added_folders.iter() {
+            writeln!(f, "+ {}/", path.join(" / "))?;
+        }
+
+        for path in self.modified_folders.iter() {
+            writeln!(f, "~ {}/", path.join(" / "))?;
+        }
+
+        for path in self.moved_folders.iter() {
+            writeln!(f, "> {}/", path.join(" / "))?;
+        }
+
+        for (path, bookmark) in self.removed_bookmarks.iter() {
+            writeln!(f, "- {}: {}", joined_title(path, bookmark), bookmark.href)?;
+        }
+
+        for (path, bookmark) in self.added_bookmarks.iter() {
+            writeln!(f, "+ {}: {}", joined_title(path, bookmark), bookmark.href)?;
+        }
+
+        for (path, _, bookmark) in self.modified_bookmarks.iter() {
+            writeln!(f, "~ {}: {}", joined_title(path, bookmark), bookmark.href)?;
+        }
+
+        for (old_path, new_path, bookmark) in self.moved_bookmarks.iter() {
+            writeln!(
+                f,
+                "> {}: {} (from {}/)",
+                joined_title(new_path, bookmark),
+                bookmark.href,
+                old_path.join(" / ")
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn joined_title(path: &[String], bookmark: &Bookmark) -> String {
+    path.iter()
+        .map(String::as_str)
+        .chain(std::iter::once(bookmark.title.as_str()))
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+fn bookmarks_with_path(children: &[Item], path: &mut Vec<String>) -> Vec<(Vec<String>, Bookmark)> {
+    let mut bookmarks = vec![];
+
+    for item in children {
+        match item {
+            Item::Subfolder(f) => {
+                path.push(f.title.clone());
+                bookmarks.append(&mut bookmarks_with_path(&f.children, path));
+                path.pop();
+            }
+            Item::Shortcut(b) => bookmarks.push((path.clone(), b.clone())),
+        }
+    }
+
+    bookmarks
+}
+
+/// Lists the nested folders without their children, with their path.
+fn folders_with_path(children: &[Item], path: &mut Vec<String>) -> Vec<(Vec<String>, Folder)> {
+    let mut folders = vec![];
+
+    for item in children {
+        if let Item::Subfolder(f) = item {
+            path.push(f.title.clone());
+            folders.push((path.clone(), f.without_children()));
+            folders.append(&mut folders_with_path(&f.children, path));
+            path.pop();
+        }
+    }
+
+    folders
+}
+
+fn same_folder_attributes(a: &Folder, b: &Folder) -> bool {
+    a.title == b.title
+        && a.folded == b.folded
+        && a.add_date == b.add_date
+        && a.last_modified == b.last_modified
+        && a.personal_toolbar_folder == b.personal_toolbar_folder
+        && a.unfiled_bookmarks_folder == b.unfiled_bookmarks_folder
+        && a.extra_attributes == b.extra_attributes
+}
+
+/// The pairs of `(before, after)` indexes, sorted by their `before` index, then the unpaired `before` and `after` indexes.
+type Pairing = (Vec<(usize, usize)>, Vec<usize>, Vec<usize>);
+
+/// Pairs the bookmarks with their identical copy first, i.e. with the same path and attributes, then by their `href`.
+fn pair_bookmarks(
+    before: &[(Vec<String>, Bookmark)],
+    after: &[(Vec<String>, Bookmark)],
+) -> Pairing {
+    let (mut pairs, removed, added) = pair_by(before, after, |(path, b)| {
+        let mut key = vec![path.join("\u{0}"), b.href.clone()];
+        key.extend(attributes(b).iter().map(|a| a.to_string()));
+        key
+    });
+
+    let unpaired_before: Vec<_> = removed.iter().map(|&b| before[b].clone()).collect();
+    let unpaired_after: Vec<_> = added.iter().map(|&a| after[a].clone()).collect();
+    let (href_pairs, still_removed, still_added) =
+        pair_by(&unpaired_before, &unpaired_after, |(_, b)| b.href.clone());
+
+    pairs.extend(href_pairs.into_iter().map(|(b, a)| (removed[b], added[a])));
+    pairs.sort_unstable();
+
+    (
+        pairs,
+        still_removed.into_iter().map(|b| removed[b]).collect(),
+        still_added.into_iter().map(|a| added[a]).collect(),
+    )
+}
+
+/// Pairs the values with the same key, the duplicated keys are paired in their order of appearance.
+fn pair_by<T, K: Eq + Hash, F: Fn(&T) -> K>(before: &[T], after: &[T], key: F) -> Pairing {
+    let mut positions: HashMap<K, VecDeque<usize>> = HashMap::new();
+
+    for (index, value) in before.iter().enumerate() {
+        positions.entry(key(value)).or_default().push_back(index);
+    }
+
+    let mut pairs = vec![];
+    let mut added = vec![];
+
+    for (index, value) in after.iter().enumerate() {
+        match positions.get_mut(&key(value)).and_then(VecDeque::pop_front) {
+            Some(position) => pairs.push((position, index)),
+            None => added.push(index),
+        }
+    }
+
+    pairs.sort_unstable();
+
+    let mut paired = vec![false; before.len()];
+
+    for &(b, _) in pairs.iter() {
+        paired[b] = true;
+    }
+
+    let removed = (0..before.len()).filter(|&b| !paired[b]).collect();
+
+    (pairs, removed, added)
+}
+
+/// Flags the pairs that keep their relative order, i.e. the longest run of pairs whose `after` indexes increase.
+/// The other pairs are the ones that moved, e.g. the reversed ones.
+fn in_order(pairs: &[(usize, usize)]) -> Vec<bool> {
+    let mut tails: Vec<usize> = vec![];
+    let mut previous = vec![None; pairs.len()];
+
+    for (index, &(_, after)) in pairs.iter().enumerate() {
+        let length = tails.partition_point(|&tail| pairs[tail].1 < after);
+
+        if length > 0 {
+            previous[index] = Some(tails[length - 1]);
+        }
+
+        if length == tails.len() {
+            tails.push(index);
+        } else {
+            tails[length] = index;
+        }
+    }
+
+    let mut flags = vec![false; pairs.len()];
+    let mut current = tails.last().copied();
+
+    while let Some(index) = current {
+        flags[index] = true;
+        current = previous[index];
+    }
+
+    flags
+}
+
+#[test]
+fn should_keep_longest_ordered_pairs() {
+    assert_eq!(
+        in_order(&[(0, 0), (1, 3), (2, 1), (3, 2)]),
+        vec![true, false, true, true]
+    );
+    assert_eq!(in_order(&[(0, 1), (1, 0)]), vec![false, true]);
+}
+
+#[test]
+fn should_describe_moved_bookmark() {
+    let rust = Bookmark::from(("Rust", "https://www.rust-lang.org/"));
+    let folder = Folder {
+        title: String::from("Code"),
+        children: vec![Item::Shortcut(rust.clone())],
+        ..Folder::default()
+    };
+
+    let preview = ChangePreview::between(&[Item::Shortcut(rust)], &[Item::Subfolder(folder)]);
+
+    assert_eq!(
+        preview.to_string(),
+        "+ Code/\n> Code / Rust: https://www.rust-lang.org/ (from /)\n"
+    );
+}
+
+#[test]
+fn should_describe_attribute_only_edit() {
+    let rust = Bookmark::from(("Rust", "https://www.rust-lang.org/"));
+    let described = Bookmark {
+        description: String::from("The Rust language"),
+        ..rust.clone()
+    };
+
+    let preview = ChangePreview::between(
+        &[Item::Shortcut(rust.clone())],
+        &[Item::Shortcut(described.clone())],
+    );
+
+    assert_eq!(preview.modified_bookmarks, vec![(vec![], rust, described)]);
+    assert!(preview.removed_bookmarks.is_empty() && preview.added_bookmarks.is_empty());
+    assert!(preview.moved_bookmarks.is_empty());
+}
+
+#[test]
+fn should_describe_reordered_items() {
+    let children = vec![
+        Item::Shortcut(Bookmark::from(("a", "https://a.example/"))),
+        Item::Shortcut(Bookmark::from(("b", "https://b.example/"))),
+        Item::Subfolder(Folder {
+            title: String::from("f"),
+            ..Folder::default()
+        }),
+        Item::Subfolder(Folder {
+            title: String::from("g"),
+            ..Folder::default()
+        }),
+    ];
+    let reversed: Vec<Item> = children.iter().rev().cloned().collect();
+
+    let preview = ChangePreview::between(&children, &reversed);
+
+    assert_eq!(preview.moved_bookmarks.len(), 1);
+    assert_eq!(preview.moved_folders.len(), 1);
+    assert!(preview.modified_bookmarks.is_empty());
+    assert!(!preview.is_empty());
+    assert!(ChangePreview::between(&children, &children).is_empty());
+}
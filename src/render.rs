@@ -0,0 +1,455 @@
+//! Contains the [RenderOptions] that customize the rendering of a [Netscape] document,
+//! and the [Renderer] trait that replaces the bundled templates at runtime.
+//!
+//! [Netscape]: crate::Netscape
+#[cfg(feature = "render")]
+use std::cell::RefCell;
+
+use crate::escape::{escape_html, escape_quotes};
+use crate::item::Item;
+use crate::{Bookmark, Folder, Netscape};
+
+/// Escapes the newlines of the text and the attribute values while a document is rendered,
+/// so that the [LineEnding] only replaces the newlines between the rendered lines.
+/// It is followed by `n` for a newline of a value, and by itself for the character itself, e.g. in a title.
+#[cfg(feature = "render")]
+pub(crate) const VALUE_ESCAPE: char = '\u{FDD0}';
+
+/// Implements the line terminators that can be used between the rendered lines.
+#[cfg(feature = "render")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// The `Lf` ending is the `\n` terminator, it is the one used by the browsers.
+    #[default]
+    Lf,
+
+    /// The `CrLf` ending is the `\r\n` terminator, it is expected by some Windows tools.
+    CrLf,
+}
+
+#[cfg(feature = "render")]
+impl LineEnding {
+    /// Gets the terminator of the line ending.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Implements the escaping policies of the text and the attribute values of the rendered items.
+#[cfg(feature = "render")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum EscapePolicy {
+    /// The `None` policy renders the text and the attribute values verbatim, as they were parsed.
+    /// A title like `a <b> & </A> c` breaks the markup, so it is only meant for the values known to be plain text.
+    None,
+
+    /// The `Html` policy escapes `&`, `<`, `>` and `"` in the text and the quotes of the attribute values,
+    /// like the Firefox and Chromium exports. It is the default, the parsed documents are rendered back as they were.
+    #[default]
+    Html,
+
+    /// The `Attribute` policy only escapes the attribute values, the titles are rendered verbatim.
+    Attribute,
+}
+
+/// Stores the options available to render a [Netscape] document.
+///
+/// [Netscape]: crate::Netscape
+#[cfg(feature = "render")]
+#[derive(Clone, Debug, Default)]
+pub struct RenderOptions {
+    /// The `line_ending` attribute is the terminator of every rendered line,
+    /// the newlines inside the text and the attribute values, e.g. a multi-line description, are kept.
+    pub line_ending: LineEnding,
+
+    /// The `escape_policy` attribute controls how the text and the attribute values are escaped.
+    pub escape_policy: EscapePolicy,
+
+    /// The `attribute_order` attribute lists the names of the `<A/>` attributes in their rendered order,
+    /// e.g. `["HREF", "ICON", "ADD_DATE"]`, the unlisted attributes follow in their default order.
+    /// By default, the order is the one of the Firefox exports : `HREF`, `ADD_DATE`, `LAST_VISIT`, `LAST_MODIFIED`,
+    /// `ICON_URI`, `ICON`, then `TARGET`, `REL` and `SHORTCUTURL`. The order is applied while the anchors are rendered.
+    pub attribute_order: Vec<String>,
+}
+
+#[cfg(feature = "render")]
+impl RenderOptions {
+    /// Applies the options on an already rendered document, line by line.
+    pub(crate) fn apply(&self, rendered: &str) -> String {
+        let mut applied = String::with_capacity(rendered.len());
+
+        for line in rendered.split_inclusive('\n') {
+            self.apply_line(line, &mut applied);
+        }
+
+        applied
+    }
+
+    /// Applies the options on a rendered line and pushes it into `applied`,
+    /// the newlines of its values are restored and its terminator is replaced by the [LineEnding].
+    /// The `attribute_order` isn't applied here but while the document is rendered, see [with_attribute_order].
+    pub(crate) fn apply_line(&self, line: &str, applied: &mut String) {
+        let (content, terminator) = match line.strip_suffix('\n') {
+            Some(content) => (content, self.line_ending.as_str()),
+            None => (line, ""),
+        };
+
+        let mut chars = content.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match (c, chars.peek()) {
+                (VALUE_ESCAPE, Some('n')) => {
+                    chars.next();
+                    applied.push('\n');
+                }
+                (VALUE_ESCAPE, Some(&VALUE_ESCAPE)) => {
+                    chars.next();
+                    applied.push(VALUE_ESCAPE);
+                }
+                (c, _) => applied.push(c),
+            }
+        }
+
+        applied.push_str(terminator);
+    }
+}
+
+#[cfg(feature = "render")]
+thread_local! {
+    /// The `attribute_order` of the [RenderOptions] being applied, see [with_attribute_order].
+    static ATTRIBUTE_ORDER: RefCell<Vec<String>> = const { RefCell::new(vec![]) };
+}
+
+/// Renders with the `order` of the `<A/>` attributes, it is read by the bookmark templates rendered by `render`.
+/// The previous order is restored afterwards, even when `render` panics.
+#[cfg(feature = "render")]
+pub(crate) fn with_attribute_order<T>(order: &[String], render: impl FnOnce() -> T) -> T {
+    struct Restore(Vec<String>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = std::mem::take(&mut self.0);
+            ATTRIBUTE_ORDER.with(|order| *order.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(ATTRIBUTE_ORDER.with(|current| current.replace(order.to_vec())));
+    render()
+}
+
+/// Sorts the `attributes` by the position of their name in the current `attribute_order`, the sort is stable
+/// and the names are compared case-insensitively : the unlisted attributes follow in their default order.
+#[cfg(feature = "render")]
+pub(crate) fn sort_by_attribute_order<T, F: Fn(&T) -> &str>(attributes: &mut [T], name: F) {
+    ATTRIBUTE_ORDER.with(|order| {
+        let order = order.borrow();
+
+        if !order.is_empty() {
+            attributes.sort_by_key(|attribute| {
+                order
+                    .iter()
+                    .position(|ordered| ordered.eq_ignore_ascii_case(name(attribute)))
+                    .unwrap_or(order.len())
+            });
+        }
+    });
+}
+
+/// Renders the items of a [Netscape] document in a custom format, in place of the bundled templates.
+///
+/// The children of a folder are rendered first, and their concatenated output is given to their parent.
+pub trait Renderer {
+    /// Renders a [Bookmark], `depth` is the number of its parent folders.
+    fn render_bookmark(&self, bookmark: &Bookmark, depth: usize) -> String;
+
+    /// Renders a [Folder] around its already rendered `children`, `depth` is the number of its parent folders.
+    fn render_folder(&self, folder: &Folder, children: &str, depth: usize) -> String;
+
+    /// Renders the whole document around its already rendered `children`, they are returned as is by default.
+    fn render_document(&self, _netscape: &Netscape, children: &str) -> String {
+        children.to_string()
+    }
+}
+
+/// Renders the document on a single line without any indentation, for the snapshot tests.
+/// The text is escaped with [escape_html] and the attribute values with [escape_quotes], like the rendered documents.
+pub(crate) struct CompactRenderer;
+
+impl CompactRenderer {
+    fn attributes(pairs: &[(&str, &str)]) -> String {
+        pairs
+            .iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(name, value)| format!(" {}=\"{}\"", name, escape_quotes(value)))
+            .collect()
+    }
+}
+
+impl Renderer for CompactRenderer {
+    fn render_bookmark(&self, bookmark: &Bookmark, _depth: usize) -> String {
+        let attributes = CompactRenderer::attributes(&[
+            ("HREF", &bookmark.href),
+            ("ADD_DATE", &bookmark.add_date),
+            ("LAST_VISIT", &bookmark.last_visit),
+            ("LAST_MODIFIED", &bookmark.last_modified),
+            ("ICON_URI", &bookmark.icon_uri),
+            ("ICON", &bookmark.icon),
+            ("TARGET", &bookmark.target),
+            ("REL", &bookmark.rel),
+            ("SHORTCUTURL", &bookmark.shortcut_url),
+        ]);
+
+        let mut rendered = format!("<DT><A{}>{}</A>", attributes, escape_html(&bookmark.title));
+
+        if !bookmark.description.is_empty() {
+            rendered.push_str(&format!("<DD>{}", escape_html(&bookmark.description)));
+        }
+
+        rendered
+    }
+
+    fn render_folder(&self, folder: &Folder, children: &str, _depth: usize) -> String {
+        let mut flags = String::new();
+
+        if folder.folded {
+            flags.push_str(" FOLDED");
+        }
+
+        let mut pairs = vec![
+            ("ADD_DATE", folder.add_date.as_str()),
+            ("LAST_MODIFIED", folder.last_modified.as_str()),
+        ];
+
+        if folder.personal_toolbar_folder {
+            pairs.push(("PERSONAL_TOOLBAR_FOLDER", "true"));
+        }
+
+        if folder.unfiled_bookmarks_folder {
+            pairs.push(("UNFILED_BOOKMARKS_FOLDER", "true"));
+        }
+
+        for (name, value) in folder.extra_attributes.iter() {
+            pairs.push((name, value));
+        }
+
+        format!(
+            "<DT><H3{}{}>{}</H3><DL>{}</DL>",
+            flags,
+            CompactRenderer::attributes(&pairs),
+            escape_html(&folder.title),
+            children
+        )
+    }
+
+    fn render_document(&self, netscape: &Netscape, children: &str) -> String {
+        let attributes = CompactRenderer::attributes(&[
+            ("ADD_DATE", &netscape.add_date),
+            ("LAST_MODIFIED", &netscape.last_modified),
+        ]);
+
+        format!(
+            "<TITLE>{}</TITLE><H1{}>{}</H1><DL>{}</DL>",
+            escape_html(&netscape.title),
+            attributes,
+            escape_html(&netscape.h1),
+            children
+        )
+    }
+}
+
+/// Renders the document as a standalone HTML5 page for browsing, the folders are collapsible `<details/>`.
+/// The folded folders start collapsed, a small inline script expands or collapses all of them.
+pub(crate) struct InteractiveRenderer;
+
+impl InteractiveRenderer {
+    fn indent(depth: usize) -> String {
+        "  ".repeat(depth + 2)
+    }
+}
+
+impl Renderer for InteractiveRenderer {
+    fn render_bookmark(&self, bookmark: &Bookmark, depth: usize) -> String {
+        format!(
+            "{}<li><a href=\"{}\">{}</a></li>\n",
+            InteractiveRenderer::indent(depth),
+            escape_html(&bookmark.href),
+            escape_html(&bookmark.title)
+        )
+    }
+
+    fn render_folder(&self, folder: &Folder, children: &str, depth: usize) -> String {
+        let indent = InteractiveRenderer::indent(depth);
+        let open = if folder.folded { "" } else { " open" };
+
+        format!(
+            "{indent}<li><details{open}><summary>{title}</summary>\n{indent}<ul>\n{children}{indent}</ul></details></li>\n",
+            indent = indent,
+            open = open,
+            title = escape_html(&folder.title),
+            children = children
+        )
+    }
+
+    fn render_document(&self, netscape: &Netscape, children: &str) -> String {
+        let title = if netscape.title.is_empty() {
+            &netscape.h1
+        } else {
+            &netscape.title
+        };
+
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="UTF-8">
+<title>{title}</title>
+<style>
+ul {{ list-style: none; padding-left: 1.5em; }}
+summary {{ cursor: pointer; font-weight: bold; }}
+</style>
+</head>
+<body>
+<h1>{h1}</h1>
+<p><button type="button" onclick="toggleAll(true)">Expand all</button> <button type="button" onclick="toggleAll(false)">Collapse all</button></p>
+<ul>
+{children}</ul>
+<script>
+function toggleAll(open) {{
+  document.querySelectorAll("details").forEach(function (details) {{ details.open = open; }});
+}}
+</script>
+</body>
+</html>
+"#,
+            title = escape_html(title),
+            h1 = escape_html(&netscape.h1),
+            children = children
+        )
+    }
+}
+
+/// Renders the `children` with the [Renderer], in the document order.
+pub(crate) fn render_children(renderer: &dyn Renderer, children: &[Item], depth: usize) -> String {
+    children
+        .iter()
+        .map(|child| match child {
+            Item::Subfolder(f) => {
+                let rendered = render_children(renderer, &f.children, depth + 1);
+                renderer.render_folder(f, &rendered, depth)
+            }
+            Item::Shortcut(b) => renderer.render_bookmark(b, depth),
+        })
+        .collect()
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_render_crlf_line_endings() {
+    let options = RenderOptions {
+        line_ending: LineEnding::CrLf,
+        ..RenderOptions::default()
+    };
+
+    assert_eq!(options.apply("a\nb\n"), "a\r\nb\r\n");
+    assert_eq!(RenderOptions::default().apply("a\nb"), "a\nb");
+    assert_eq!(
+        options.apply("<DD>a\u{FDD0}nb\u{FDD0}\u{FDD0}n\n"),
+        "<DD>a\nb\u{FDD0}n\r\n"
+    );
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn should_reorder_anchor_attributes() {
+    let options = RenderOptions {
+        attribute_order: vec![String::from("icon"), String::from("HREF")],
+        escape_policy: EscapePolicy::None,
+        ..RenderOptions::default()
+    };
+    let netscape = Netscape {
+        children: vec![Item::Shortcut(Bookmark {
+            href: String::from("url"),
+            add_date: String::from("1"),
+            last_modified: String::from("2"),
+            icon: String::from("a b"),
+            title: String::from("see <A HREF=\"other\" ICON=\"c\">"),
+            ..Bookmark::default()
+        })],
+        ..Netscape::default()
+    };
+    let rendered = netscape.to_html_with(&options).unwrap();
+
+    assert!(rendered.contains(
+        r#"<DT><A ICON="a b" HREF="url" ADD_DATE="1" LAST_MODIFIED="2">see <A HREF="other" ICON="c"></A>"#
+    ));
+    assert!(netscape
+        .to_html()
+        .unwrap()
+        .contains(r#"<DT><A HREF="url" ADD_DATE="1" LAST_MODIFIED="2" ICON="a b">"#));
+}
+
+#[test]
+fn should_render_nested_children_with_depth() {
+    struct Outline;
+
+    impl Renderer for Outline {
+        fn render_bookmark(&self, bookmark: &Bookmark, depth: usize) -> String {
+            format!("{}{};", depth, bookmark.title)
+        }
+
+        fn render_folder(&self, folder: &Folder, children: &str, depth: usize) -> String {
+            format!("{}{}[{}]", depth, folder.title, children)
+        }
+    }
+
+    let children = vec![
+        Item::Subfolder(Folder {
+            title: String::from("f"),
+            children: vec![Item::Shortcut(Bookmark::from(("b", "url")))],
+            ..Folder::default()
+        }),
+        Item::Shortcut(Bookmark::from(("c", "url"))),
+    ];
+
+    assert_eq!(render_children(&Outline, &children, 0), "0f[1b;]0c;");
+}
+
+#[test]
+fn should_render_compact_single_line() {
+    let netscape = Netscape {
+        title: String::from("Bookmarks"),
+        h1: String::from("Bookmarks"),
+        children: vec![Item::Subfolder(Folder {
+            title: String::from("f"),
+            folded: true,
+            personal_toolbar_folder: true,
+            children: vec![Item::Shortcut(Bookmark::from(("b", "url")))],
+            ..Folder::default()
+        })],
+        ..Netscape::default()
+    };
+
+    assert_eq!(
+        netscape.render_with(&CompactRenderer),
+        r#"<TITLE>Bookmarks</TITLE><H1>Bookmarks</H1><DL><DT><H3 FOLDED PERSONAL_TOOLBAR_FOLDER="true">f</H3><DL><DT><A HREF="url">b</A></DL></DL>"#
+    );
+}
+
+#[test]
+fn should_escape_compact_markup() {
+    let netscape = Netscape {
+        children: vec![Item::Shortcut(Bookmark::from((
+            "<b> & co",
+            "https://example.com/?a=1&b=\"2\"",
+        )))],
+        ..Netscape::default()
+    };
+
+    assert_eq!(
+        netscape.render_with(&CompactRenderer),
+        r#"<TITLE></TITLE><H1></H1><DL><DT><A HREF="https://example.com/?a=1&b=&quot;2&quot;">&lt;b&gt; &amp; co</A></DL>"#
+    );
+}
@@ -0,0 +1,85 @@
+//! Contains the [BrowserHint] guessed from the markers left by the browsers in their exports.
+
+/// Implements the browsers that can be recognized from an exported document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum BrowserHint {
+    /// The `Firefox` hint is given by its `Bookmarks Menu` heading and its unfiled bookmarks folder.
+    Firefox,
+
+    /// The `Chrome` hint is given by its `Bookmarks bar` toolbar folder, or its dates in milliseconds.
+    Chrome,
+
+    /// The `Edge` hint is given by its `Favorites bar` toolbar folder.
+    Edge,
+
+    /// The `Safari` hint is given by a document without a generated comment nor any dates.
+    Safari,
+
+    /// The `Unknown` hint is given when no marker was found.
+    #[default]
+    Unknown,
+}
+
+/// Guesses the browser that exported the raw document, the markers are searched case-insensitively.
+pub(crate) fn detect_source(raw: &str) -> BrowserHint {
+    let upper = raw.to_uppercase();
+
+    if upper.contains("<H1>BOOKMARKS MENU</H1>")
+        || upper.contains("UNFILED_BOOKMARKS_FOLDER")
+        || upper.contains("LAST_CHARSET=")
+        || upper.contains("HREF=\"PLACE:")
+    {
+        return BrowserHint::Firefox;
+    }
+
+    if upper.contains(">FAVORITES BAR</H3>") {
+        return BrowserHint::Edge;
+    }
+
+    if upper.contains(">BOOKMARKS BAR</H3>") || has_millisecond_dates(&upper) {
+        return BrowserHint::Chrome;
+    }
+
+    if upper.contains("<DT>")
+        && !upper.contains("AUTOMATICALLY GENERATED")
+        && !upper.contains("ADD_DATE=")
+    {
+        return BrowserHint::Safari;
+    }
+
+    if upper.contains("PERSONAL_TOOLBAR_FOLDER") {
+        return BrowserHint::Chrome;
+    }
+
+    BrowserHint::Unknown
+}
+
+/// Checks if any `ADD_DATE` is too long to be a timestamp in seconds.
+fn has_millisecond_dates(upper: &str) -> bool {
+    upper
+        .split("ADD_DATE=\"")
+        .skip(1)
+        .any(|rest| rest.chars().take_while(|c| c.is_ascii_digit()).count() >= 13)
+}
+
+#[test]
+fn should_detect_fixture_sources() {
+    let firefox = std::fs::read_to_string("./res/firefox.html").unwrap();
+    let chromium = std::fs::read_to_string("./res/chromium.html").unwrap();
+
+    assert_eq!(detect_source(&firefox), BrowserHint::Firefox);
+    assert_eq!(detect_source(&chromium), BrowserHint::Chrome);
+}
+
+#[test]
+fn should_detect_other_sources() {
+    let edge = r#"<DT><H3 ADD_DATE="1600910474" PERSONAL_TOOLBAR_FOLDER="true">Favorites bar</H3>"#;
+    let safari = r#"<HTML><DL><DT><A HREF="https://www.apple.com/">Apple</A></DL>"#;
+    let milliseconds =
+        r#"<!-- This is an automatically generated file. --><DT><A ADD_DATE="1600910474000">"#;
+
+    assert_eq!(detect_source(edge), BrowserHint::Edge);
+    assert_eq!(detect_source(safari), BrowserHint::Safari);
+    assert_eq!(detect_source(milliseconds), BrowserHint::Chrome);
+    assert_eq!(detect_source(""), BrowserHint::Unknown);
+}
@@ -0,0 +1,20 @@
+//! Contains the [Stats] struct that summarizes the contents of a [Netscape] document.
+//!
+//! [Netscape]: crate::Netscape
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Represents the counts of the items of a document, see [Netscape::stats].
+///
+/// [Netscape::stats]: crate::Netscape::stats
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Stats {
+    /// The count of all the nested bookmarks.
+    pub bookmarks: usize,
+
+    /// The count of all the nested folders.
+    pub folders: usize,
+
+    /// The count of bookmarks of every host, the hosts are lower-cased and the invalid urls are skipped.
+    pub domains: BTreeMap<String, usize>,
+}
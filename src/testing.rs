@@ -0,0 +1,48 @@
+//! Contains the helpers that check the fidelity of the crate against real documents, e.g. a corpus of browser exports.
+//!
+//! It is enabled by the `testing` feature.
+use crate::Netscape;
+
+/// Parses a raw document, renders it, parses it back and asserts that both models are equal.
+///
+/// It panics with the first differing field and its path of child indexes, see [Netscape::roundtrip_check].
+///
+/// ```rust
+/// use bookmarkt::testing::assert_roundtrips;
+/// use std::fs;
+///
+/// for entry in fs::read_dir("./res").unwrap() {
+///     let path = entry.unwrap().path();
+///
+///     if path.extension().is_some_and(|e| e == "html") {
+///         assert_roundtrips(&fs::read_to_string(path).unwrap());
+///     }
+/// }
+/// ```
+pub fn assert_roundtrips(raw: &str) {
+    let netscape = match Netscape::from_html(raw) {
+        Ok(netscape) => netscape,
+        Err(e) => panic!("the document can't be parsed: {}", e),
+    };
+
+    if let Err(difference) = netscape.roundtrip_check() {
+        panic!("the document doesn't round-trip: {}", difference);
+    }
+}
+
+#[test]
+fn should_roundtrip_the_corpus() {
+    for entry in std::fs::read_dir("./res").unwrap() {
+        let path = entry.unwrap().path();
+
+        if path.extension().is_some_and(|e| e == "html") {
+            assert_roundtrips(&std::fs::read_to_string(path).unwrap());
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "the document doesn't round-trip")]
+fn should_panic_on_lossy_roundtrip() {
+    assert_roundtrips("<DL><p><DT><A HREF=\"url\">a&#13;b</A></DL><p>");
+}